@@ -0,0 +1,44 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+// Hot paths that run without a Vulkan device.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use vma::*;
+
+fn allocate_free(c: &mut Criterion) {
+    let mut block =
+        VirtualBlock::create(&VirtualBlockCreateInfo::new().with_size(64 << 20)).unwrap();
+    let create_info = VirtualAllocationCreateInfo::new()
+        .with_size(256)
+        .with_alignment(16);
+
+    c.bench_function("virtual allocate and free", |b| {
+        b.iter(|| {
+            let (allocation, offset) = block.allocate(black_box(&create_info)).unwrap();
+            black_box(offset);
+            block.free(allocation);
+        })
+    });
+
+    block.destroy();
+}
+
+fn transient_plan(c: &mut Criterion) {
+    let resources: Vec<_> = (0..64u32)
+        .map(|index| TransientResource {
+            size: (1 + index as u64 % 7) << 20,
+            alignment: 1 << 16,
+            memory_type_bits: !0,
+            first_pass: index / 2,
+            last_pass: index / 2 + index % 5,
+        })
+        .collect();
+
+    c.bench_function("transient plan 64 resources", |b| {
+        b.iter(|| TransientLayout::plan(black_box(&resources)).unwrap())
+    });
+}
+
+criterion_group!(benches, allocate_free, transient_plan);
+criterion_main!(benches);
@@ -1,7 +1,6 @@
 // Copyright (c) 2024 Jacob R. Green
 // All rights reserved.
 
-use bindgen;
 use bindgen::callbacks::EnumVariantValue;
 use std::collections::HashMap;
 use std::env::var;
@@ -53,6 +52,8 @@ fn main() {
         .generate()
         .unwrap();
 
+    probe_header_functions(&vma_header_path);
+
     let out_path = PathBuf::from(var("OUT_DIR").unwrap());
 
     let prefix_map = build_config_map();
@@ -84,6 +85,44 @@ fn main() {
     }
 }
 
+/// Functions newer than the oldest supported header, with the cfg set when the header has them
+/// and the preprocessor guard they are declared under, if any.
+const PROBED_FUNCTIONS: &[(&str, &str, Option<&str>)] = &[
+    ("vmaCopyMemoryToAllocation", "vma_copy_memory", None),
+    (
+        "vmaGetMemoryWin32Handle",
+        "vma_memory_win32_handle",
+        Some("VMA_EXTERNAL_MEMORY_WIN32"),
+    ),
+];
+
+fn probe_header_functions(header_path: &PathBuf) {
+    let header = std::fs::read_to_string(header_path).unwrap();
+
+    for (function, cfg, guard) in PROBED_FUNCTIONS {
+        println!("cargo:rustc-check-cfg=cfg({})", cfg);
+        let declared = header.contains(&format!("{}(", function));
+        if declared && guard.is_none_or(guard_enabled) {
+            println!("cargo:rustc-cfg={}", cfg);
+        }
+    }
+}
+
+/// Whether a header guard is on. An environment variable of the same name forces it, matching the
+/// define VMA was compiled with. Otherwise `VMA_EXTERNAL_MEMORY_WIN32` is on for Windows targets.
+fn guard_enabled(guard: &str) -> bool {
+    println!("cargo:rerun-if-env-changed={}", guard);
+    match var(guard) {
+        Ok(value) => value != "0",
+        Err(_) => match guard {
+            "VMA_EXTERNAL_MEMORY_WIN32" => {
+                var("CARGO_CFG_TARGET_OS").is_ok_and(|os| os == "windows")
+            }
+            _ => false,
+        },
+    }
+}
+
 fn format_enum_name(name: &str) -> String {
     name.trim_start_matches("Vma").to_string()
 }
@@ -96,7 +135,7 @@ fn format_enum_variant_name(prefix: &str, name: &str) -> Option<String> {
             return None;
         }
     };
-    if formatted.chars().next().unwrap().is_digit(10) {
+    if formatted.chars().next().unwrap().is_ascii_digit() {
         formatted = name.strip_prefix(&prefix[..prefix.len() - 1]).unwrap();
     }
     Some(formatted.to_string())
@@ -109,14 +148,11 @@ fn format_flag_enum_name(name: &str) -> String {
 }
 
 fn format_flag_variant_name(prefix: &str, name: &str) -> Option<String> {
-    let mut formatted = match name.strip_prefix(prefix) {
-        Some(stripped) => stripped,
-        None => return None,
-    };
-    if formatted.chars().next().unwrap().is_digit(10) {
+    let mut formatted = name.strip_prefix(prefix)?;
+    if formatted.chars().next().unwrap().is_ascii_digit() {
         formatted = name.strip_prefix(&prefix[..prefix.len() - 1]).unwrap();
     }
-    let formatted = formatted.replace("_BIT", "");
+    let formatted = formatted.strip_suffix("_BIT").unwrap_or(formatted);
     Some(formatted.to_string())
 }
 
@@ -135,10 +171,29 @@ fn write_enum<'a, W: Write, I: Iterator<Item = &'a Enum>>(
         .map(|s| s.to_string())
         .unwrap_or_else(|| format_enum_name(enum_config.name));
 
-    writeln!(writer, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
-    writeln!(writer, "#[repr(i32)]").unwrap();
-    writeln!(writer, "pub enum {} {{", new_name).unwrap();
-    for variant in variants.map(|e| e.0.as_str()).filter(filter_enum_variant) {
+    // A newtype with constants rather than an enum, so values from newer VMA headers are kept
+    // instead of being transmuted into an invalid discriminant.
+    writeln!(
+        writer,
+        "#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]"
+    )
+    .unwrap();
+    writeln!(writer, "#[repr(transparent)]").unwrap();
+    writeln!(writer, "pub struct {}(i32);", new_name).unwrap();
+    writeln!(
+        writer,
+        "assert_eq_size!({}, {});",
+        new_name, enum_config.name
+    )
+    .unwrap();
+
+    writeln!(writer, "impl {} {{", new_name).unwrap();
+    let mut written = Vec::new();
+    let mut values = Vec::new();
+    for (variant, value) in variants
+        .map(|e| (e.0.as_str(), e.1))
+        .filter(|e| filter_enum_variant(&e.0))
+    {
         // cargo_warning!("{}: {}", enum_name, variant);
         if let Some(skip) = skip {
             if skip.contains(&variant) {
@@ -146,30 +201,96 @@ fn write_enum<'a, W: Write, I: Iterator<Item = &'a Enum>>(
             }
         }
 
+        // Aliases would make the Debug and is_known matches unreachable.
+        if values.contains(&value) {
+            continue;
+        }
+        values.push(value);
+
         let formatted = match format_enum_variant_name(enum_config.prefix, variant) {
             Some(formatted) => formatted,
             None => continue,
         };
 
-        writeln!(writer, "    {} = {},", formatted, variant).unwrap();
+        if let Some((_, note)) = enum_config.deprecated.iter().find(|d| d.0 == variant) {
+            writeln!(writer, "    #[deprecated(note = \"{}\")]", note).unwrap();
+        }
+        writeln!(
+            writer,
+            "    pub const {}: Self = Self({});",
+            formatted, variant
+        )
+        .unwrap();
+        written.push(formatted);
+    }
+    writeln!(
+        writer,
+        "    pub const fn from_raw(value: i32) -> Self {{ Self(value) }}"
+    )
+    .unwrap();
+    writeln!(writer, "    pub const fn as_raw(&self) -> i32 {{ self.0 }}").unwrap();
+    writeln!(
+        writer,
+        "    /// Whether the value is one of the constants known to this crate's VMA header."
+    )
+    .unwrap();
+    writeln!(writer, "    pub const fn is_known(&self) -> bool {{").unwrap();
+    if written.is_empty() {
+        writeln!(writer, "        false").unwrap();
+    } else {
+        let patterns = written
+            .iter()
+            .map(|formatted| format!("Self::{}", formatted))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        writeln!(writer, "        matches!(*self, {})", patterns).unwrap();
     }
+    writeln!(writer, "    }}").unwrap();
     writeln!(writer, "}}").unwrap();
+
+    writeln!(writer, "impl TryFrom<i32> for {} {{", new_name).unwrap();
+    writeln!(writer, "    type Error = InvalidEnumValue;").unwrap();
     writeln!(
         writer,
-        "assert_eq_size!({}, {});",
-        new_name, enum_config.name
+        "    fn try_from(value: i32) -> Result<Self, Self::Error> {{"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "        if Self(value).is_known() {{ Ok(Self(value)) }} else {{ Err(InvalidEnumValue(value)) }}"
     )
     .unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}}").unwrap();
 
-    writeln!(writer, "impl {} {{", new_name).unwrap();
+    writeln!(writer, "impl std::fmt::Debug for {} {{", new_name).unwrap();
+    writeln!(
+        writer,
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{"
+    )
+    .unwrap();
+    writeln!(writer, "        match *self {{").unwrap();
+    for formatted in &written {
+        writeln!(
+            writer,
+            "            Self::{0} => f.write_str(\"{0}\"),",
+            formatted
+        )
+        .unwrap();
+    }
     writeln!(
         writer,
-        "    pub const fn from_raw(value: i32) -> Self {{ unsafe {{ std::mem::transmute(value) }} }}"
+        "            Self(value) => write!(f, \"Unknown({{}})\", value),"
     )
     .unwrap();
+    writeln!(writer, "        }}").unwrap();
+    writeln!(writer, "    }}").unwrap();
+    writeln!(writer, "}}").unwrap();
+
+    writeln!(writer, "impl std::fmt::Display for {} {{", new_name).unwrap();
     writeln!(
         writer,
-        "    pub const fn as_raw(&self) -> i32 {{ *self as i32 }}"
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{ std::fmt::Debug::fmt(self, f) }}"
     )
     .unwrap();
     writeln!(writer, "}}").unwrap();
@@ -187,31 +308,60 @@ fn write_flags<'a, W: Write, I: Iterator<Item = &'a Enum>>(
         .unwrap_or_else(|| format_flag_enum_name(enum_config.name));
 
     writeln!(writer, "bitflags! {{").unwrap();
-    writeln!(writer, "    #[derive(Default, Clone, Copy, PartialEq, Eq)]").unwrap();
+    writeln!(
+        writer,
+        "    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]"
+    )
+    .unwrap();
     writeln!(writer, "    pub struct {}: u32 {{", enum_name).unwrap();
 
-    for variant in variants.map(|e| e.0.as_str()).filter(filter_enum_variant) {
+    // FLAG_BITS_MAX_ENUM only forces the C enum to 32 bits; as a flag it would set every bit.
+    let mut written: Vec<(String, EnumVariantValue)> = Vec::new();
+    for (variant, value) in variants
+        .map(|e| (e.0.as_str(), e.1))
+        .filter(|e| filter_enum_variant(&e.0))
+    {
         // cargo_warning!("{}: {}", enum_name, variant);
-        if variant.contains("MAX_ENUM") {
-            continue;
-        }
-
         let formatted = match format_flag_variant_name(enum_config.prefix, variant) {
             Some(formatted) => formatted,
             None => continue,
         };
 
+        if formatted.ends_with("_MASK") {
+            writeln!(
+                writer,
+                "        /// Every bit of the {} group.",
+                &formatted[..formatted.len() - 5]
+            )
+            .unwrap();
+        } else if let Some((original, _)) = written.iter().find(|e| e.1 == value) {
+            writeln!(writer, "        /// Alias of `{}`.", original).unwrap();
+        }
         writeln!(writer, "        const {} = {} as u32;", formatted, variant).unwrap();
+        written.push((formatted, value));
     }
 
     writeln!(writer, "    }}").unwrap();
     writeln!(writer, "}}").unwrap();
 
-    // writeln!(writer, "impl {} {{", enum_name).unwrap();
-    // writeln!(
-    //     writer,
-    //     "    pub const fn from_raw(value: u32) -> Self {{ Self::from_bits_truncate(value) }}"
-    // )
+    writeln!(writer, "impl {} {{", enum_name).unwrap();
+    writeln!(
+        writer,
+        "    pub const fn from_raw(value: u32) -> Self {{ Self::from_bits_truncate(value) }}"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    pub const fn try_from_raw(value: u32) -> Option<Self> {{ Self::from_bits(value) }}"
+    )
+    .unwrap();
+    writeln!(
+        writer,
+        "    pub const fn as_raw(&self) -> u32 {{ self.bits() }}"
+    )
+    .unwrap();
+    writeln!(writer, "}}").unwrap();
+
     writeln!(
         writer,
         "assert_eq_size!({}, {});",
@@ -227,9 +377,9 @@ struct FormatCallback {
     cargo_callbacks: bindgen::CargoCallbacks,
 }
 
+/// Aliases sharing a value are kept, flags emit them as constants and plain enums skip them.
 fn push_enum_variant(vec: &mut EnumVec, variant: &str, value: EnumVariantValue) {
-    if vec.iter().map(|e| e.1).any(|e| e == value) {
-        // cargo_warning!("Duplicate value found for variant: {}", variant);
+    if vec.iter().any(|e| e.0 == variant) {
         return;
     }
 
@@ -257,9 +407,7 @@ impl bindgen::callbacks::ParseCallbacks for FormatCallback {
         }
 
         let mut map = self.enum_map.lock().unwrap();
-        let vec = map
-            .entry(trimmed_enum_name.to_string())
-            .or_insert_with(Vec::new);
+        let vec = map.entry(trimmed_enum_name.to_string()).or_default();
         push_enum_variant(vec, original_variant_name, variant_value);
 
         None
@@ -274,23 +422,13 @@ impl bindgen::callbacks::ParseCallbacks for FormatCallback {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 struct EnumConfig<'a> {
     name: &'a str,
     custom_name: Option<&'a str>,
     prefix: &'a str,
     is_flags: bool,
-}
-
-impl Default for EnumConfig<'_> {
-    fn default() -> Self {
-        Self {
-            name: "",
-            custom_name: None,
-            prefix: "",
-            is_flags: false,
-        }
-    }
+    deprecated: &'a [(&'a str, &'a str)],
 }
 
 fn build_config_map() -> HashMap<&'static str, EnumConfig<'static>> {
@@ -298,6 +436,28 @@ fn build_config_map() -> HashMap<&'static str, EnumConfig<'static>> {
         EnumConfig {
             name: "VmaMemoryUsage",
             prefix: "VMA_MEMORY_USAGE_",
+            deprecated: &[
+                (
+                    "VMA_MEMORY_USAGE_GPU_ONLY",
+                    "use AUTO_PREFER_DEVICE, see AllocationCreateInfo::auto_prefer_device()",
+                ),
+                (
+                    "VMA_MEMORY_USAGE_CPU_ONLY",
+                    "use AUTO with HOST_ACCESS_SEQUENTIAL_WRITE, see AllocationCreateInfo::host_sequential_write()",
+                ),
+                (
+                    "VMA_MEMORY_USAGE_CPU_TO_GPU",
+                    "use AUTO with HOST_ACCESS_SEQUENTIAL_WRITE, see AllocationCreateInfo::host_sequential_write()",
+                ),
+                (
+                    "VMA_MEMORY_USAGE_GPU_TO_CPU",
+                    "use AUTO with HOST_ACCESS_RANDOM, see AllocationCreateInfo::host_random_access()",
+                ),
+                (
+                    "VMA_MEMORY_USAGE_CPU_COPY",
+                    "use AUTO_PREFER_HOST, see AllocationCreateInfo::auto_prefer_host()",
+                ),
+            ],
             ..Default::default()
         },
         EnumConfig {
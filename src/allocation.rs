@@ -7,43 +7,295 @@ use vma_sys::*;
 
 vma_handle!(Allocation, VmaAllocation);
 
-vma_struct!(AllocationInfo, VmaAllocationInfo);
+vma_struct!(AllocationInfo, VmaAllocationInfo, Clone, unsafe);
 
-vma_struct!(AllocationCreateInfo, VmaAllocationCreateInfo);
+impl AllocationInfo {
+    pub const fn memory_type(&self) -> u32 {
+        self.inner.memoryType
+    }
+
+    pub const fn device_memory(&self) -> vk::DeviceMemory {
+        vk::DeviceMemory::from_raw(self.inner.deviceMemory)
+    }
+
+    pub const fn offset(&self) -> u64 {
+        self.inner.offset
+    }
+
+    pub const fn size(&self) -> u64 {
+        self.inner.size
+    }
+
+    pub const fn mapped_data(&self) -> *mut std::ffi::c_void {
+        self.inner.pMappedData
+    }
+
+    /// The name set with `Allocator::set_allocation_name`, or `None` if unset or not valid UTF-8.
+    /// Copied out, since VMA frees the string on rename or when the allocation is freed. The info
+    /// must not be older than the last rename, see `Allocator::allocation_name`.
+    pub fn name(&self) -> Option<String> {
+        if self.inner.pName.is_null() {
+            return None;
+        }
+        unsafe { std::ffi::CStr::from_ptr(self.inner.pName) }
+            .to_str()
+            .ok()
+            .map(str::to_owned)
+    }
+
+    pub const fn user_data(&self) -> *mut std::ffi::c_void {
+        self.inner.pUserData
+    }
+
+    /// # Safety
+    /// The user data must be null or point to a live `T`, as passed to
+    /// `AllocationCreateInfo::with_user_data` or `Allocator::set_allocation_user_data`.
+    pub unsafe fn user_data_as<T>(&self) -> Option<&T> {
+        (self.inner.pUserData as *const T).as_ref()
+    }
+}
+
+#[derive(Debug)]
+pub struct AllocatedBuffer {
+    pub buffer: vk::Buffer,
+    pub allocation: Allocation,
+    pub info: AllocationInfo,
+}
+
+#[derive(Debug)]
+pub struct AllocatedImage {
+    pub image: vk::Image,
+    pub allocation: Allocation,
+    pub info: AllocationInfo,
+}
+
+vma_struct!(AllocationInfo2, VmaAllocationInfo2, Clone, unsafe);
+
+impl AllocationInfo2 {
+    pub const fn allocation_info(&self) -> &AllocationInfo {
+        // `AllocationInfo` is a transparent wrapper of `VmaAllocationInfo`.
+        unsafe {
+            &*(&self.inner.allocationInfo as *const VmaAllocationInfo as *const AllocationInfo)
+        }
+    }
+
+    pub const fn block_size(&self) -> u64 {
+        self.inner.blockSize
+    }
+
+    pub const fn dedicated_memory(&self) -> bool {
+        self.inner.dedicatedMemory != vk::sys::VK_FALSE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationCreateInfoError {
+    ConflictingHostAccess,
+    MappedWithoutHostAccess,
+    TransferInsteadWithoutHostAccess,
+    NeverAllocateWithDedicated,
+}
+
+impl std::fmt::Display for AllocationCreateInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConflictingHostAccess => write!(
+                f,
+                "HOST_ACCESS_SEQUENTIAL_WRITE and HOST_ACCESS_RANDOM are mutually exclusive"
+            ),
+            Self::MappedWithoutHostAccess => write!(
+                f,
+                "MAPPED with an AUTO memory usage requires a HOST_ACCESS_* flag"
+            ),
+            Self::TransferInsteadWithoutHostAccess => write!(
+                f,
+                "HOST_ACCESS_ALLOW_TRANSFER_INSTEAD requires a HOST_ACCESS_* flag"
+            ),
+            Self::NeverAllocateWithDedicated => write!(
+                f,
+                "NEVER_ALLOCATE and DEDICATED_MEMORY are mutually exclusive"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AllocationCreateInfoError {}
+
+vma_struct!(AllocationCreateInfo, VmaAllocationCreateInfo, Clone);
 
 impl AllocationCreateInfo {
-    pub fn with_usage(mut self, usage: MemoryUsage) -> Self {
+    pub const AUTO: Self = Self::new().with_usage(MemoryUsage::AUTO);
+
+    pub const AUTO_PREFER_DEVICE: Self = Self::new().with_usage(MemoryUsage::AUTO_PREFER_DEVICE);
+
+    pub const AUTO_PREFER_HOST: Self = Self::new().with_usage(MemoryUsage::AUTO_PREFER_HOST);
+
+    /// Device-local memory that is never touched by the host.
+    pub const GPU_ONLY: Self = Self::AUTO_PREFER_DEVICE;
+
+    /// Host-visible memory written once, linearly, e.g. staging and uniform buffers.
+    pub const HOST_SEQUENTIAL_WRITE: Self =
+        Self::AUTO.with_creation_flags(AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE);
+
+    /// Host-visible memory read back or written in random order, e.g. readback buffers.
+    pub const HOST_RANDOM_ACCESS: Self =
+        Self::AUTO.with_creation_flags(AllocationCreateFlags::HOST_ACCESS_RANDOM);
+
+    /// Render targets that live only within a render pass, e.g. MSAA color or depth on tilers.
+    /// Prefers lazily allocated tile memory and falls back to plain device-local memory where
+    /// there is none. Images must have `TRANSIENT_ATTACHMENT` usage. Also works as the allocation
+    /// create info of a `PoolBuilder`.
+    pub const TRANSIENT_ATTACHMENT: Self = Self::AUTO_PREFER_DEVICE.with_preferred_flags(
+        vk::MemoryPropertyFlags::LAZILY_ALLOCATED.union(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+    );
+
+    pub const fn auto() -> Self {
+        Self::AUTO
+    }
+
+    pub const fn auto_prefer_device() -> Self {
+        Self::AUTO_PREFER_DEVICE
+    }
+
+    pub const fn auto_prefer_host() -> Self {
+        Self::AUTO_PREFER_HOST
+    }
+
+    pub const fn host_sequential_write() -> Self {
+        Self::HOST_SEQUENTIAL_WRITE
+    }
+
+    pub const fn host_random_access() -> Self {
+        Self::HOST_RANDOM_ACCESS
+    }
+
+    pub const fn transient_attachment() -> Self {
+        Self::TRANSIENT_ATTACHMENT
+    }
+
+    pub const fn with_usage(mut self, usage: MemoryUsage) -> Self {
         self.inner.usage = usage.as_raw();
         self
     }
 
-    pub fn with_required_flags(mut self, flags: vk::MemoryPropertyFlags) -> Self {
+    pub const fn with_required_flags(mut self, flags: vk::MemoryPropertyFlags) -> Self {
         self.inner.requiredFlags = flags.bits();
         self
     }
 
-    pub fn with_preferred_flags(mut self, flags: vk::MemoryPropertyFlags) -> Self {
+    pub const fn with_preferred_flags(mut self, flags: vk::MemoryPropertyFlags) -> Self {
         self.inner.preferredFlags = flags.bits();
         self
     }
 
-    pub fn with_creation_flags(mut self, flags: AllocationCreateFlags) -> Self {
+    pub const fn with_creation_flags(mut self, flags: AllocationCreateFlags) -> Self {
         self.inner.flags = flags.bits();
         self
     }
 
-    pub fn with_memory_type_bits(mut self, bits: u32) -> Self {
+    /// Replaces any strategy bits already set, leaving the other flags alone.
+    pub const fn with_strategy(mut self, strategy: AllocationCreateFlags) -> Self {
+        let mask = AllocationCreateFlags::STRATEGY_MASK.bits();
+        self.inner.flags = (self.inner.flags & !mask) | (strategy.bits() & mask);
+        self
+    }
+
+    pub const fn with_memory_type_bits(mut self, bits: u32) -> Self {
+        self.inner.memoryTypeBits = bits;
+        self
+    }
+
+    /// Lets the memory be aliased by several resources, see `create_aliasing_buffer2`.
+    pub const fn allow_aliasing(mut self) -> Self {
+        self.inner.flags |= AllocationCreateFlags::CAN_ALIAS.bits();
+        self
+    }
+
+    /// Fails instead of exceeding the heap budget.
+    pub const fn within_budget(mut self) -> Self {
+        self.inner.flags |= AllocationCreateFlags::WITHIN_BUDGET.bits();
+        self
+    }
+
+    /// Only suballocates from existing blocks, never allocating new device memory.
+    pub const fn never_allocate(mut self) -> Self {
+        self.inner.flags |= AllocationCreateFlags::NEVER_ALLOCATE.bits();
+        self
+    }
+
+    /// Gives the resource its own `VkDeviceMemory`.
+    pub const fn dedicated(mut self) -> Self {
+        self.inner.flags |= AllocationCreateFlags::DEDICATED_MEMORY.bits();
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), AllocationCreateInfoError> {
+        let flags = AllocationCreateFlags::from_bits_truncate(self.inner.flags);
+        let host_access = AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE
+            | AllocationCreateFlags::HOST_ACCESS_RANDOM;
+
+        if flags.contains(host_access) {
+            return Err(AllocationCreateInfoError::ConflictingHostAccess);
+        }
+
+        if flags.contains(
+            AllocationCreateFlags::NEVER_ALLOCATE | AllocationCreateFlags::DEDICATED_MEMORY,
+        ) {
+            return Err(AllocationCreateInfoError::NeverAllocateWithDedicated);
+        }
+
+        if self.is_auto()
+            && flags.contains(AllocationCreateFlags::MAPPED)
+            && !flags.intersects(host_access)
+        {
+            return Err(AllocationCreateInfoError::MappedWithoutHostAccess);
+        }
+
+        if flags.contains(AllocationCreateFlags::HOST_ACCESS_ALLOW_TRANSFER_INSTEAD)
+            && !flags.intersects(host_access)
+        {
+            return Err(AllocationCreateInfoError::TransferInsteadWithoutHostAccess);
+        }
+
+        Ok(())
+    }
+
+    /// Whether the usage is one of the AUTO values, which VMA can only resolve with a buffer or
+    /// image create info.
+    pub fn is_auto(&self) -> bool {
+        matches!(
+            MemoryUsage::from_raw(self.inner.usage),
+            MemoryUsage::AUTO | MemoryUsage::AUTO_PREFER_DEVICE | MemoryUsage::AUTO_PREFER_HOST
+        )
+    }
+
+    /// Narrows `memoryTypeBits` to the types allowed by `requirements`. A value of zero means
+    /// "any type" to VMA, so it is replaced rather than masked.
+    pub fn restrict_to_requirements(mut self, requirements: &vk::MemoryRequirements) -> Self {
+        let allowed = requirements.as_raw().memoryTypeBits;
+        let bits = match self.inner.memoryTypeBits {
+            0 => allowed,
+            bits => bits & allowed,
+        };
+
+        debug_assert!(
+            bits != 0,
+            "memoryTypeBits {:#x} has no type in common with the requirements' {:#x}",
+            self.inner.memoryTypeBits,
+            allowed
+        );
+
         self.inner.memoryTypeBits = bits;
         self
     }
 
-    //     pub fn with_pool(mut self, pool: Pool) -> Self {
-    //         self.inner.pool = pool.as_raw();
-    //         self
-    //     }
-    //
-    //     pub fn with_user_data(mut self, user_data: *mut std::ffi::c_void) -> Self {
-    //         self.inner.pUserData = user_data;
-    //         self
-    //     }
+    pub const fn with_pool(mut self, pool: Pool) -> Self {
+        self.inner.pool = pool.as_raw();
+        self
+    }
+
+    pub fn with_user_data<T>(mut self, user_data: *mut T) -> Self {
+        self.inner.pUserData = user_data.cast();
+        self
+    }
 }
@@ -3,34 +3,156 @@
 
 use crate::macros::*;
 use crate::*;
+use std::cell::Cell;
 use std::ffi::c_void;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
+use std::sync::Arc;
+use std::time::Duration;
 use vma_sys::*;
 
 vma_handle!(Allocator, VmaAllocator);
 
 impl crate::allocator::Allocator {
-    pub fn create(create_info: &AllocatorCreateInfo) -> Result<Self, ()> {
+    /// Fails with [`Error::InvalidCreateInfo`] if the instance, physical device or device is
+    /// missing, rather than letting VMA dereference null. See [`AllocatorCreateInfo::try_build`].
+    pub fn create(create_info: &AllocatorCreateInfo) -> Result<Self, Error> {
+        if let Err(err) = create_info.validate_handles() {
+            trace!(%err, "allocator creation rejected");
+            return Err(Error::InvalidCreateInfo(err));
+        }
+
         let mut allocator = std::ptr::null_mut();
 
         let result = unsafe { vmaCreateAllocator(create_info.as_raw(), &mut allocator) };
         if result != vk::sys::VK_SUCCESS {
-            return Err(());
+            return Err(Error::from_raw(result));
+        }
+
+        let allocator = unsafe { Self::from_raw(allocator) };
+        create_state::record(allocator, create_info);
+        block_size::set_large_heap_block_size(
+            allocator,
+            create_info.inner.preferredLargeHeapBlockSize,
+        );
+
+        #[cfg(feature = "tracing")]
+        for warning in allocator.block_size_warnings() {
+            tracing::warn!(
+                heap = warning.heap_index,
+                heap_size = warning.heap_size,
+                block_size = warning.block_size,
+                "{}",
+                warning
+            );
         }
 
-        Ok(Self::from_raw(allocator))
+        Ok(allocator)
+    }
+
+    /// Creates a reference-counted allocator, destroyed when the last clone is dropped.
+    pub fn create_shared(create_info: &AllocatorCreateInfo) -> Result<SharedAllocator, Error> {
+        Ok(SharedAllocator {
+            owner: Arc::new(AllocatorOwner(Self::create(create_info)?)),
+        })
+    }
+
+    /// Creates an allocator without VMA's internal mutexes, see [`AllocatorUnsync`].
+    pub fn create_externally_synchronized(
+        create_info: &AllocatorCreateInfo,
+    ) -> Result<AllocatorUnsync, Error> {
+        let mut create_info = create_info.clone();
+        create_info.inner.flags |= AllocatorCreateFlags::EXTERNALLY_SYNCHRONIZED.bits();
+
+        Ok(AllocatorUnsync {
+            allocator: Self::create(&create_info)?,
+            _unsync: PhantomData,
+        })
     }
 
     pub fn destroy(self) {
+        map_tracking::forget_allocator(self);
+        tracking::forget_allocator(self);
+        generation::forget_allocator(self);
+        block_size::forget_allocator(self);
+        create_state::forget_allocator(self);
+        #[cfg(feature = "profiling")]
+        profiling::forget_allocator(self);
+        dedicated::set_threshold(self, None);
         unsafe { vmaDestroyAllocator(self.as_raw()) };
     }
 
+    /// Destroys the allocator, first reporting any allocations still alive. Unlike VMA's own leak
+    /// assert this also works in release builds.
+    pub fn destroy_checked(self) -> Result<(), LeakReport> {
+        let report = self.check_no_allocations().err();
+
+        #[cfg(feature = "tracing")]
+        if let Some(report) = &report {
+            tracing::warn!(
+                count = report.allocation_count,
+                bytes = report.allocation_bytes,
+                "{}",
+                report
+            );
+        }
+
+        self.destroy();
+        report.map_or(Ok(()), Err)
+    }
+
+    /// Reports any allocations still alive, without destroying the allocator. Meant for test
+    /// teardown, before [`Allocator::destroy`]. The leaked allocations are only listed with
+    /// [`Allocator::enable_object_tracking`]; the counts are always filled in.
+    pub fn check_no_allocations(&self) -> Result<(), LeakReport> {
+        let total = self.calculate_statistics().total.statistics;
+        if total.allocation_count == 0 {
+            return Ok(());
+        }
+
+        Err(LeakReport {
+            allocation_count: total.allocation_count,
+            allocation_bytes: total.allocation_bytes,
+            allocations: self
+                .tracked_objects()
+                .into_iter()
+                .filter_map(|(id, object)| match object {
+                    TrackedObject::Allocation(allocation) => {
+                        let info = self.get_allocation_info(allocation);
+                        Some(LeakedAllocation {
+                            id,
+                            allocation,
+                            size: info.size(),
+                            name: info.name(),
+                        })
+                    }
+                    TrackedObject::Pool(_) => None,
+                })
+                .collect(),
+        })
+    }
+
     pub fn create_buffer(
         &self,
         buffer_create_info: &vk::BufferCreateInfo,
         allocation_create_info: &AllocationCreateInfo,
         allocation_info: Option<&mut AllocationInfo>,
-    ) -> Result<(vk::Buffer, Allocation), ()> {
+    ) -> Result<(vk::Buffer, Allocation), CreateError> {
+        self.create_buffer_raw(
+            buffer_create_info.as_raw(),
+            allocation_create_info,
+            allocation_info,
+        )
+    }
+
+    /// Creates a buffer straight through VMA, skipping debug validation, the dedicated
+    /// threshold, id tracking and tracing. Meant for hot loops with known-good create infos.
+    pub fn create_buffer_unchecked(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Buffer, Allocation), Error> {
         let mut buffer = std::ptr::null_mut();
         let mut allocation = std::ptr::null_mut();
 
@@ -41,30 +163,251 @@ impl crate::allocator::Allocator {
                 allocation_create_info.as_raw(),
                 &mut buffer,
                 &mut allocation,
-                transmute(allocation_info),
+                allocation_info
+                    .map_or(std::ptr::null_mut(), |info| std::ptr::from_mut(info).cast()),
             )
         };
 
         if result != vk::sys::VK_SUCCESS {
-            return Err(());
+            return Err(Error::from_raw(result));
         }
 
-        Ok((
-            vk::Buffer::from_raw(buffer),
-            Allocation::from_raw(allocation),
-        ))
+        Ok((vk::Buffer::from_raw(buffer), unsafe {
+            Allocation::from_raw(allocation)
+        }))
+    }
+
+    /// Tries `primary`, then each of `fallbacks` in turn while creation fails for lack of memory.
+    /// Returns the index of the create info that succeeded, 0 being `primary`.
+    pub fn create_buffer_with_fallback(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        primary: &AllocationCreateInfo,
+        fallbacks: &[AllocationCreateInfo],
+        mut allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Buffer, Allocation, usize), CreateError> {
+        let mut last_err = None;
+        for (index, allocation_create_info) in std::iter::once(primary).chain(fallbacks).enumerate()
+        {
+            match self.create_buffer(
+                buffer_create_info,
+                allocation_create_info,
+                allocation_info.as_deref_mut(),
+            ) {
+                Ok((buffer, allocation)) => return Ok((buffer, allocation, index)),
+                Err(err) if err.error.is_out_of_memory() => {
+                    trace!(index, err = %err, "retrying buffer creation with fallback");
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_err.unwrap())
+    }
+
+    pub fn create_allocated_buffer(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<AllocatedBuffer, CreateError> {
+        let mut info = AllocationInfo::new();
+        let (buffer, allocation) =
+            self.create_buffer(buffer_create_info, allocation_create_info, Some(&mut info))?;
+
+        Ok(AllocatedBuffer {
+            buffer,
+            allocation,
+            info,
+        })
+    }
+
+    /// Creates a buffer and queries its device address. The buffer usage must include
+    /// `SHADER_DEVICE_ADDRESS`. The allocator must be created by this crate with
+    /// `BUFFER_DEVICE_ADDRESS` and a function table, which `vkGetBufferDeviceAddress` is loaded
+    /// through; otherwise this fails with [`Error::MissingFunction`].
+    pub fn create_buffer_with_address(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Buffer, Allocation, vk::sys::VkDeviceAddress), CreateError> {
+        let raw_create_info = buffer_create_info.as_raw();
+        let fail = |error| {
+            let request = CreateRequest::Buffer {
+                size: raw_create_info.size,
+                usage: raw_create_info.usage,
+            };
+            CreateError::new(error, request, allocation_create_info)
+        };
+
+        let usage = vk::sys::VK_BUFFER_USAGE_SHADER_DEVICE_ADDRESS_BIT as u32;
+        if raw_create_info.usage & usage == 0 {
+            return Err(fail(Error::MissingBufferUsage(usage)));
+        }
+
+        let get_buffer_device_address = create_state::get(*self)
+            .filter(|state| {
+                state
+                    .flags
+                    .contains(AllocatorCreateFlags::BUFFER_DEVICE_ADDRESS)
+            })
+            .and_then(|state| state.get_buffer_device_address)
+            .ok_or_else(|| fail(Error::MissingFunction("vkGetBufferDeviceAddress")))?;
+
+        let (buffer, allocation) =
+            self.create_buffer(buffer_create_info, allocation_create_info, None)?;
+
+        let address_info = vk::sys::VkBufferDeviceAddressInfo {
+            sType: vk::sys::VK_STRUCTURE_TYPE_BUFFER_DEVICE_ADDRESS_INFO,
+            pNext: std::ptr::null(),
+            buffer: buffer.as_raw(),
+        };
+        let address =
+            unsafe { get_buffer_device_address(self.raw_allocator_info().device, &address_info) };
+
+        Ok((buffer, allocation, address))
+    }
+
+    /// Creates a buffer whose size is rounded up by `buckets` before allocating. VMA sizes the
+    /// memory from the buffer, so the buffer itself is created with the rounded size, not the
+    /// requested one. Images are not covered, as their memory size is only known from the driver.
+    pub fn create_buffer_bucketed(
+        &self,
+        buckets: &SizeBuckets,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Buffer, Allocation), CreateError> {
+        let mut raw_create_info = *buffer_create_info.as_raw();
+        raw_create_info.size = buckets.round(raw_create_info.size);
+
+        self.create_buffer_raw(&raw_create_info, allocation_create_info, allocation_info)
+    }
+
+    /// Allocates memory for `memory_requirements` with the size rounded up by `buckets`. Only the
+    /// allocation grows, resources bound to it keep their own size.
+    pub fn allocate_memory_bucketed(
+        &self,
+        buckets: &SizeBuckets,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<Allocation, CreateError> {
+        let mut raw_requirements = *memory_requirements.as_raw();
+        raw_requirements.size = buckets.round(raw_requirements.size);
+
+        self.allocate_memory(
+            &vk::MemoryRequirements::from_raw(raw_requirements),
+            allocation_create_info,
+            allocation_info,
+        )
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn create_buffer_raw(
+        &self,
+        buffer_create_info: &vk::sys::VkBufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Buffer, Allocation), CreateError> {
+        let request = CreateRequest::Buffer {
+            size: buffer_create_info.size,
+            usage: buffer_create_info.usage,
+        };
+
+        let dedicated_create_info =
+            dedicated::apply(*self, buffer_create_info.size, allocation_create_info);
+        let allocation_create_info = dedicated_create_info
+            .as_ref()
+            .unwrap_or(allocation_create_info);
+
+        #[cfg(debug_assertions)]
+        if let Err(err) = allocation_create_info.validate() {
+            panic!("invalid AllocationCreateInfo: {}", err);
+        }
+
+        #[cfg(feature = "validation")]
+        limits::check_buffer(None, buffer_create_info).map_err(|err| {
+            CreateError::new(Error::LimitExceeded(err), request, allocation_create_info)
+        })?;
+
+        let mut buffer = std::ptr::null_mut();
+        let mut allocation = std::ptr::null_mut();
+
+        let result = unsafe {
+            vmaCreateBuffer(
+                self.as_raw(),
+                buffer_create_info,
+                allocation_create_info.as_raw(),
+                &mut buffer,
+                &mut allocation,
+                allocation_info
+                    .map_or(std::ptr::null_mut(), |info| std::ptr::from_mut(info).cast()),
+            )
+        };
+
+        if result != vk::sys::VK_SUCCESS {
+            trace!(
+                result,
+                size = buffer_create_info.size,
+                "buffer creation failed"
+            );
+            return Err(CreateError::new(
+                Error::from_raw(result),
+                request,
+                allocation_create_info,
+            ));
+        }
+
+        let allocation = unsafe { Allocation::from_raw(allocation) };
+        tracking::register(*self, allocation.into());
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("buffer created", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Created, allocation);
+
+        Ok((vk::Buffer::from_raw(buffer), allocation))
     }
 
     pub fn destroy_buffer(&self, buffer: vk::Buffer, allocation: Allocation) {
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("buffer destroyed", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Destroyed, allocation);
+        map_tracking::forget_allocation(*self, allocation);
+        generation::forget_allocation(*self, allocation);
+        tracking::unregister(*self, allocation.into());
         unsafe { vmaDestroyBuffer(self.as_raw(), buffer.as_raw(), allocation.as_raw()) };
     }
 
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn create_image(
         &self,
         image_create_info: &vk::ImageCreateInfo,
         allocation_create_info: &AllocationCreateInfo,
         allocation_info: Option<&mut AllocationInfo>,
-    ) -> Result<(vk::Image, Allocation), ()> {
+    ) -> Result<(vk::Image, Allocation), CreateError> {
+        let raw_create_info = image_create_info.as_raw();
+        let request = CreateRequest::Image {
+            extent: [
+                raw_create_info.extent.width,
+                raw_create_info.extent.height,
+                raw_create_info.extent.depth,
+            ],
+            format: raw_create_info.format,
+            usage: raw_create_info.usage,
+        };
+
+        #[cfg(debug_assertions)]
+        if let Err(err) = allocation_create_info.validate() {
+            panic!("invalid AllocationCreateInfo: {}", err);
+        }
+
+        #[cfg(feature = "validation")]
+        self.validate_image_info(image_create_info, None)
+            .map_err(|err| {
+                CreateError::new(Error::LimitExceeded(err), request, allocation_create_info)
+            })?;
+
         let mut image = std::ptr::null_mut();
         let mut allocation = std::ptr::null_mut();
 
@@ -75,38 +418,1316 @@ impl crate::allocator::Allocator {
                 allocation_create_info.as_raw(),
                 &mut image,
                 &mut allocation,
-                transmute(allocation_info),
+                allocation_info
+                    .map_or(std::ptr::null_mut(), |info| std::ptr::from_mut(info).cast()),
             )
         };
 
         if result != vk::sys::VK_SUCCESS {
-            return Err(());
+            trace!(result, "image creation failed");
+            return Err(CreateError::new(
+                Error::from_raw(result),
+                request,
+                allocation_create_info,
+            ));
+        }
+
+        let allocation = unsafe { Allocation::from_raw(allocation) };
+        tracking::register(*self, allocation.into());
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("image created", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Created, allocation);
+
+        Ok((vk::Image::from_raw(image), allocation))
+    }
+
+    /// Like [`Self::create_buffer_with_fallback`], for images.
+    pub fn create_image_with_fallback(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        primary: &AllocationCreateInfo,
+        fallbacks: &[AllocationCreateInfo],
+        mut allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Image, Allocation, usize), CreateError> {
+        let mut last_err = None;
+        for (index, allocation_create_info) in std::iter::once(primary).chain(fallbacks).enumerate()
+        {
+            match self.create_image(
+                image_create_info,
+                allocation_create_info,
+                allocation_info.as_deref_mut(),
+            ) {
+                Ok((image, allocation)) => return Ok((image, allocation, index)),
+                Err(err) if err.error.is_out_of_memory() => {
+                    trace!(index, err = %err, "retrying image creation with fallback");
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
         }
+        Err(last_err.unwrap())
+    }
+
+    pub fn create_allocated_image(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<AllocatedImage, CreateError> {
+        let mut info = AllocationInfo::new();
+        let (image, allocation) =
+            self.create_image(image_create_info, allocation_create_info, Some(&mut info))?;
 
-        Ok((vk::Image::from_raw(image), Allocation::from_raw(allocation)))
+        Ok(AllocatedImage {
+            image,
+            allocation,
+            info,
+        })
     }
 
     pub fn destroy_image(&self, image: vk::Image, allocation: Allocation) {
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("image destroyed", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Destroyed, allocation);
+        map_tracking::forget_allocation(*self, allocation);
+        generation::forget_allocation(*self, allocation);
+        tracking::unregister(*self, allocation.into());
         unsafe { vmaDestroyImage(self.as_raw(), image.as_raw(), allocation.as_raw()) };
     }
 
-    pub fn map_memory<'a>(&self, allocation: Allocation) -> Result<NonNull<c_void>, ()> {
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn allocate_memory(
+        &self,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<Allocation, CreateError> {
+        let raw_requirements = memory_requirements.as_raw();
+        let request = CreateRequest::Memory {
+            size: raw_requirements.size,
+            alignment: raw_requirements.alignment,
+            memory_type_bits: raw_requirements.memoryTypeBits,
+        };
+
+        let dedicated_create_info =
+            dedicated::apply(*self, raw_requirements.size, allocation_create_info);
+        let allocation_create_info = dedicated_create_info
+            .as_ref()
+            .unwrap_or(allocation_create_info);
+
+        #[cfg(debug_assertions)]
+        if let Err(err) = allocation_create_info.validate() {
+            panic!("invalid AllocationCreateInfo: {}", err);
+        }
+
+        let mut allocation = std::ptr::null_mut();
+
+        let result = unsafe {
+            vmaAllocateMemory(
+                self.as_raw(),
+                memory_requirements.as_raw(),
+                allocation_create_info.as_raw(),
+                &mut allocation,
+                allocation_info
+                    .map_or(std::ptr::null_mut(), |info| std::ptr::from_mut(info).cast()),
+            )
+        };
+
+        if result != vk::sys::VK_SUCCESS {
+            trace!(
+                result,
+                size = memory_requirements.as_raw().size,
+                "memory allocation failed"
+            );
+            return Err(CreateError::new(
+                Error::from_raw(result),
+                request,
+                allocation_create_info,
+            ));
+        }
+
+        let allocation = unsafe { Allocation::from_raw(allocation) };
+        tracking::register(*self, allocation.into());
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("memory allocated", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Created, allocation);
+
+        Ok(allocation)
+    }
+
+    pub fn free_memory(&self, allocation: Allocation) {
+        #[cfg(feature = "tracing")]
+        self.trace_allocation("memory freed", allocation);
+        #[cfg(feature = "profiling")]
+        profiling::allocation_event(*self, AllocationEventKind::Destroyed, allocation);
+        map_tracking::forget_allocation(*self, allocation);
+        generation::forget_allocation(*self, allocation);
+        tracking::unregister(*self, allocation.into());
+        unsafe { vmaFreeMemory(self.as_raw(), allocation.as_raw()) };
+    }
+
+    pub fn create_aliasing_buffer2(
+        &self,
+        allocation: Allocation,
+        allocation_local_offset: u64,
+        buffer_create_info: &vk::BufferCreateInfo,
+    ) -> Result<vk::Buffer, Error> {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let mut buffer = std::ptr::null_mut();
+
+        let result = unsafe {
+            vmaCreateAliasingBuffer2(
+                self.as_raw(),
+                allocation.as_raw(),
+                allocation_local_offset,
+                buffer_create_info.as_raw(),
+                &mut buffer,
+            )
+        };
+
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(vk::Buffer::from_raw(buffer))
+    }
+
+    pub fn create_aliasing_image2(
+        &self,
+        allocation: Allocation,
+        allocation_local_offset: u64,
+        image_create_info: &vk::ImageCreateInfo,
+    ) -> Result<vk::Image, Error> {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let mut image = std::ptr::null_mut();
+
+        let result = unsafe {
+            vmaCreateAliasingImage2(
+                self.as_raw(),
+                allocation.as_raw(),
+                allocation_local_offset,
+                image_create_info.as_raw(),
+                &mut image,
+            )
+        };
+
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(vk::Image::from_raw(image))
+    }
+
+    /// Buffers and memory allocations larger than `bytes` always get dedicated memory, while
+    /// smaller ones keep whatever their create info asks for. Images are not affected since their
+    /// size is only known once they are created.
+    pub fn set_dedicated_threshold(&self, bytes: u64) {
+        dedicated::set_threshold(*self, Some(bytes));
+    }
+
+    pub fn clear_dedicated_threshold(&self) {
+        dedicated::set_threshold(*self, None);
+    }
+
+    pub fn dedicated_threshold(&self) -> Option<u64> {
+        dedicated::threshold(*self)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn create_pool(&self, create_info: &PoolCreateInfo) -> Result<Pool, Error> {
+        #[cfg(debug_assertions)]
+        if let Err(err) = create_info.validate() {
+            panic!("invalid PoolCreateInfo: {}", err);
+        }
+
+        let mut pool = std::ptr::null_mut();
+
+        let result = unsafe { vmaCreatePool(self.as_raw(), create_info.as_raw(), &mut pool) };
+        if result != vk::sys::VK_SUCCESS {
+            trace!(
+                result,
+                memory_type = create_info.as_raw().memoryTypeIndex,
+                "pool creation failed"
+            );
+            return Err(Error::from_raw(result));
+        }
+
+        let pool = unsafe { Pool::from_raw(pool) };
+        tracking::register(*self, pool.into());
+        trace!(
+            %pool,
+            id = self.pool_id(pool),
+            memory_type = create_info.as_raw().memoryTypeIndex,
+            block_size = create_info.as_raw().blockSize,
+            "pool created"
+        );
+
+        Ok(pool)
+    }
+
+    pub fn destroy_pool(&self, pool: Pool) {
+        trace!(
+            %pool,
+            id = tracking::id_of(*self, pool.into()),
+            "pool destroyed"
+        );
+        tracking::unregister(*self, pool.into());
+        unsafe { vmaDestroyPool(self.as_raw(), pool.as_raw()) };
+    }
+
+    pub fn find_memory_type_index(
+        &self,
+        memory_type_bits: u32,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<u32, Error> {
+        let mut index = 0;
+
+        let result = unsafe {
+            vmaFindMemoryTypeIndex(
+                self.as_raw(),
+                memory_type_bits,
+                allocation_create_info.as_raw(),
+                &mut index,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(index)
+    }
+
+    pub fn find_memory_type_index_for_buffer_info(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<u32, Error> {
+        let mut index = 0;
+
+        let result = unsafe {
+            vmaFindMemoryTypeIndexForBufferInfo(
+                self.as_raw(),
+                buffer_create_info.as_raw(),
+                allocation_create_info.as_raw(),
+                &mut index,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(index)
+    }
+
+    pub fn find_memory_type_index_for_image_info(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<u32, Error> {
+        let mut index = 0;
+
+        let result = unsafe {
+            vmaFindMemoryTypeIndexForImageInfo(
+                self.as_raw(),
+                image_create_info.as_raw(),
+                allocation_create_info.as_raw(),
+                &mut index,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(index)
+    }
+
+    pub fn begin_defragmentation(
+        &self,
+        info: &DefragmentationInfo,
+    ) -> Result<DefragmentationContext, Error> {
+        let mut context = std::ptr::null_mut();
+
+        let result = unsafe { vmaBeginDefragmentation(self.as_raw(), info.as_raw(), &mut context) };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(unsafe { DefragmentationContext::from_raw(context) })
+    }
+
+    pub fn end_defragmentation(&self, context: DefragmentationContext) -> DefragmentationStats {
+        let mut stats = unsafe { std::mem::zeroed() };
+        unsafe { vmaEndDefragmentation(self.as_raw(), context.as_raw(), &mut stats) };
+        stats.into()
+    }
+
+    /// Returns `None` once there is nothing left to move.
+    pub fn begin_defragmentation_pass(
+        &self,
+        context: DefragmentationContext,
+    ) -> Result<Option<DefragmentationPass>, Error> {
+        let mut pass = DefragmentationPass::new();
+
+        let result = unsafe {
+            vmaBeginDefragmentationPass(
+                self.as_raw(),
+                context.as_raw(),
+                std::ptr::from_mut(&mut pass).cast(),
+            )
+        };
+        match result {
+            vk::sys::VK_SUCCESS => Ok(None),
+            vk::sys::VK_INCOMPLETE => {
+                trace!(%context, moves = pass.moves().len(), "defragmentation pass");
+                Ok(Some(pass))
+            }
+            result => Err(Error::from_raw(result)),
+        }
+    }
+
+    /// Returns `true` when defragmentation is complete and `false` when another pass is needed.
+    pub fn end_defragmentation_pass(
+        &self,
+        context: DefragmentationContext,
+        pass: &mut DefragmentationPass,
+    ) -> Result<bool, Error> {
+        let moves_with = |operation| -> Vec<_> {
+            pass.moves()
+                .iter()
+                .filter(|m| m.operation() == operation)
+                .map(DefragmentationMove::src_allocation)
+                .collect()
+        };
+        let moved = moves_with(DefragmentationMoveOperation::COPY);
+        // VMA frees the source of a DESTROY move when the pass ends.
+        let destroyed = moves_with(DefragmentationMoveOperation::DESTROY);
+
+        let result = unsafe {
+            vmaEndDefragmentationPass(
+                self.as_raw(),
+                context.as_raw(),
+                std::ptr::from_mut(pass).cast(),
+            )
+        };
+        let done = match result {
+            vk::sys::VK_SUCCESS => true,
+            vk::sys::VK_INCOMPLETE => false,
+            result => return Err(Error::from_raw(result)),
+        };
+
+        for allocation in moved {
+            generation::bump(*self, allocation);
+        }
+        for allocation in destroyed {
+            map_tracking::forget_allocation(*self, allocation);
+            generation::forget_allocation(*self, allocation);
+            tracking::unregister(*self, allocation.into());
+        }
+        Ok(done)
+    }
+
+    pub fn allocation_version(&self, allocation: Allocation) -> AllocationVersion {
+        generation::version(*self, allocation)
+    }
+
+    /// Whether defragmentation has moved `allocation` since `version` was taken.
+    pub fn allocation_changed(&self, allocation: Allocation, version: AllocationVersion) -> bool {
+        self.allocation_version(allocation) != version
+    }
+
+    #[cfg(feature = "tracing")]
+    fn trace_allocation(&self, message: &str, allocation: Allocation) {
+        let info = self.get_allocation_info(allocation);
+        let name = info.name();
+
+        tracing::debug!(
+            %allocation,
+            id = self.allocation_id(allocation),
+            size = info.size(),
+            memory_type = info.memory_type(),
+            name = name.as_deref(),
+            "{}",
+            message
+        );
+    }
+
+    /// Starts assigning ids to allocations and pools created through this allocator, for
+    /// [`Self::allocation_id`], [`Self::allocation_is_valid`] and leak reports. Tracking is off by
+    /// default, and untracked creates and destroys take no lock. Objects created before tracking
+    /// was enabled get no id.
+    pub fn enable_object_tracking(&self) {
+        tracking::enable(*self);
+    }
+
+    /// Stops tracking and forgets every id.
+    pub fn disable_object_tracking(&self) {
+        tracking::forget_allocator(*self);
+    }
+
+    /// Whether `allocation` is a live allocation created through this allocator. Only covers
+    /// allocators with [`Self::enable_object_tracking`]; everything else, including allocations
+    /// from `create_buffer_unchecked` or the raw bindings, reports `false`.
+    pub fn allocation_is_valid(&self, allocation: Allocation) -> bool {
+        if allocation.is_null() || tracking::id_of(*self, allocation.into()).is_none() {
+            return false;
+        }
+        self.get_allocation_info(allocation).size() != 0
+    }
+
+    pub fn allocation_id(&self, allocation: Allocation) -> Option<u64> {
+        tracking::id_of(*self, allocation.into())
+    }
+
+    pub fn pool_id(&self, pool: Pool) -> Option<u64> {
+        tracking::id_of(*self, pool.into())
+    }
+
+    pub fn object_by_id(&self, id: u64) -> Option<TrackedObject> {
+        tracking::object_by_id(*self, id)
+    }
+
+    /// Live allocations and pools of this allocator, ordered by id.
+    pub fn tracked_objects(&self) -> Vec<(u64, TrackedObject)> {
+        tracking::objects(*self)
+    }
+
+    pub fn get_allocation_info(&self, allocation: Allocation) -> AllocationInfo {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let mut info = AllocationInfo::new();
+        unsafe {
+            vmaGetAllocationInfo(
+                self.as_raw(),
+                allocation.as_raw(),
+                std::ptr::from_mut(&mut info).cast(),
+            )
+        };
+        info
+    }
+
+    /// See [`AllocationInfoCache`].
+    pub fn cache_allocation_info(&self, allocation: Allocation) -> AllocationInfoCache {
+        AllocationInfoCache::new(self, allocation)
+    }
+
+    pub fn get_allocation_info2(&self, allocation: Allocation) -> AllocationInfo2 {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let mut info = AllocationInfo2::new();
+        unsafe {
+            vmaGetAllocationInfo2(
+                self.as_raw(),
+                allocation.as_raw(),
+                std::ptr::from_mut(&mut info).cast(),
+            )
+        };
+        info
+    }
+
+    pub fn set_allocation_name(&self, allocation: Allocation, name: &std::ffi::CStr) {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        unsafe { vmaSetAllocationName(self.as_raw(), allocation.as_raw(), name.as_ptr()) };
+    }
+
+    /// The current name of `allocation`, read while it is known to be live.
+    pub fn allocation_name(&self, allocation: Allocation) -> Option<String> {
+        self.get_allocation_info(allocation).name()
+    }
+
+    pub fn set_allocation_user_data<T>(&self, allocation: Allocation, user_data: *mut T) {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        unsafe { vmaSetAllocationUserData(self.as_raw(), allocation.as_raw(), user_data.cast()) };
+    }
+
+    pub(crate) fn raw_allocator_info(&self) -> VmaAllocatorInfo {
+        let mut info = unsafe { std::mem::zeroed() };
+        unsafe { vmaGetAllocatorInfo(self.as_raw(), &mut info) };
+        info
+    }
+
+    pub(crate) fn raw_memory_properties(&self) -> vk::sys::VkPhysicalDeviceMemoryProperties {
+        let mut properties = std::ptr::null();
+        unsafe {
+            vmaGetMemoryProperties(self.as_raw(), &mut properties);
+            *properties
+        }
+    }
+
+    pub fn memory_heaps(&self) -> impl Iterator<Item = MemoryHeap> {
+        let properties = self.raw_memory_properties();
+        (0..properties.memoryHeapCount).map(move |index| {
+            let heap = properties.memoryHeaps[index as usize];
+            MemoryHeap {
+                index,
+                size: heap.size,
+                flags: vk::MemoryHeapFlags::from_bits_truncate(heap.flags),
+            }
+        })
+    }
+
+    pub fn memory_types(&self) -> impl Iterator<Item = MemoryType> {
+        let properties = self.raw_memory_properties();
+        (0..properties.memoryTypeCount).map(move |index| {
+            let memory_type = properties.memoryTypes[index as usize];
+            MemoryType {
+                index,
+                heap_index: memory_type.heapIndex,
+                property_flags: vk::MemoryPropertyFlags::from_bits_truncate(
+                    memory_type.propertyFlags,
+                ),
+            }
+        })
+    }
+
+    /// The preferred (maximum) block size of `heap_index`, following VMA's own heuristic: an
+    /// eighth of the heap for heaps up to [`SMALL_HEAP_MAX_SIZE`], the preferred large heap block
+    /// size otherwise. New blocks of default pools may start smaller. Heap sizes already account for any heap size limit. Custom pools with a
+    /// fixed block size are not affected.
+    pub fn calc_preferred_block_size(&self, heap_index: u32) -> u64 {
+        let properties = self.raw_memory_properties();
+        assert!(
+            heap_index < properties.memoryHeapCount,
+            "heap index {} out of range",
+            heap_index
+        );
+        preferred_block_size(
+            properties.memoryHeaps[heap_index as usize].size,
+            block_size::large_heap_block_size(*self),
+        )
+    }
+
+    /// The heaps too small for their preferred block size. Logged on creation with the
+    /// `tracing` feature.
+    pub fn block_size_warnings(&self) -> Vec<BlockSizeWarning> {
+        let large_heap_block_size = block_size::large_heap_block_size(*self);
+        self.memory_heaps()
+            .filter_map(|heap| {
+                let block_size = preferred_block_size(heap.size, large_heap_block_size);
+                (block_size > heap.size).then_some(BlockSizeWarning {
+                    heap_index: heap.index,
+                    heap_size: heap.size,
+                    block_size,
+                })
+            })
+            .collect()
+    }
+
+    /// Granularity at which linear and optimal-tiling resources in the same memory must not
+    /// share a page. VMA pads between them unless a pool ignores it.
+    pub fn buffer_image_granularity(&self) -> u64 {
+        self.raw_device_limits().bufferImageGranularity
+    }
+
+    pub(crate) fn raw_device_limits(&self) -> vk::sys::VkPhysicalDeviceLimits {
+        let mut properties = std::ptr::null();
+        unsafe {
+            vmaGetPhysicalDeviceProperties(self.as_raw(), &mut properties);
+            (*properties).limits
+        }
+    }
+
+    /// Checks that `create_info` has a size, and that it fits `maxBufferSize` when
+    /// `get_physical_device_properties2` is given (Vulkan 1.3 or `VK_KHR_maintenance4`). Done
+    /// automatically on creation with the `validation` feature, without `maxBufferSize`.
+    pub fn validate_buffer_info(
+        &self,
+        create_info: &vk::BufferCreateInfo,
+        get_physical_device_properties2: vk::sys::PFN_vkGetPhysicalDeviceProperties2,
+    ) -> Result<(), LimitError> {
+        let max_buffer_size = limits::max_buffer_size(
+            self.raw_allocator_info().physicalDevice,
+            get_physical_device_properties2,
+        );
+        limits::check_buffer(max_buffer_size, create_info.as_raw())
+    }
+
+    /// Checks `create_info` against the device limits, and against the format's supported usage,
+    /// extent, mip levels and array layers when `get_physical_device_image_format_properties`
+    /// is given.
+    pub fn validate_image_info(
+        &self,
+        create_info: &vk::ImageCreateInfo,
+        get_physical_device_image_format_properties: vk::sys::PFN_vkGetPhysicalDeviceImageFormatProperties,
+    ) -> Result<(), LimitError> {
+        let create_info = create_info.as_raw();
+        let format_properties = limits::image_format_properties(
+            self.raw_allocator_info().physicalDevice,
+            get_physical_device_image_format_properties,
+            create_info,
+        )?;
+        limits::check_image(
+            &self.raw_device_limits(),
+            format_properties.as_ref(),
+            create_info,
+        )
+    }
+
+    /// The memory requirements a buffer would have, without creating it. Needs Vulkan 1.3 or
+    /// `VK_KHR_maintenance4`. VMA does not expose its function table, so the caller passes the
+    /// entry point it loaded.
+    pub fn buffer_memory_requirements(
+        &self,
+        create_info: &vk::BufferCreateInfo,
+        get_device_buffer_memory_requirements: vk::sys::PFN_vkGetDeviceBufferMemoryRequirements,
+    ) -> Result<vk::MemoryRequirements, Error> {
+        let get_device_buffer_memory_requirements = get_device_buffer_memory_requirements.ok_or(
+            Error::MissingFunction("vkGetDeviceBufferMemoryRequirements"),
+        )?;
+
+        let info = vk::sys::VkDeviceBufferMemoryRequirements {
+            sType: vk::sys::VK_STRUCTURE_TYPE_DEVICE_BUFFER_MEMORY_REQUIREMENTS,
+            pNext: std::ptr::null(),
+            pCreateInfo: create_info.as_raw(),
+        };
+        let mut requirements: vk::sys::VkMemoryRequirements2 = unsafe { std::mem::zeroed() };
+        requirements.sType = vk::sys::VK_STRUCTURE_TYPE_MEMORY_REQUIREMENTS_2;
+
+        unsafe {
+            get_device_buffer_memory_requirements(
+                self.raw_allocator_info().device,
+                &info,
+                &mut requirements,
+            )
+        };
+
+        Ok(vk::MemoryRequirements::from_raw(
+            requirements.memoryRequirements,
+        ))
+    }
+
+    /// Like [`Self::buffer_memory_requirements`], for a non-disjoint image.
+    pub fn image_memory_requirements(
+        &self,
+        create_info: &vk::ImageCreateInfo,
+        get_device_image_memory_requirements: vk::sys::PFN_vkGetDeviceImageMemoryRequirements,
+    ) -> Result<vk::MemoryRequirements, Error> {
+        let get_device_image_memory_requirements = get_device_image_memory_requirements
+            .ok_or(Error::MissingFunction("vkGetDeviceImageMemoryRequirements"))?;
+
+        let info = vk::sys::VkDeviceImageMemoryRequirements {
+            sType: vk::sys::VK_STRUCTURE_TYPE_DEVICE_IMAGE_MEMORY_REQUIREMENTS,
+            pNext: std::ptr::null(),
+            pCreateInfo: create_info.as_raw(),
+            planeAspect: 0,
+        };
+        let mut requirements: vk::sys::VkMemoryRequirements2 = unsafe { std::mem::zeroed() };
+        requirements.sType = vk::sys::VK_STRUCTURE_TYPE_MEMORY_REQUIREMENTS_2;
+
+        unsafe {
+            get_device_image_memory_requirements(
+                self.raw_allocator_info().device,
+                &info,
+                &mut requirements,
+            )
+        };
+
+        Ok(vk::MemoryRequirements::from_raw(
+            requirements.memoryRequirements,
+        ))
+    }
+
+    pub fn calculate_statistics(&self) -> TotalStatistics {
+        let properties = self.raw_memory_properties();
+        let mut stats = unsafe { std::mem::zeroed() };
+        unsafe { vmaCalculateStatistics(self.as_raw(), &mut stats) };
+
+        TotalStatistics::from_raw(
+            &stats,
+            properties.memoryTypeCount as usize,
+            properties.memoryHeapCount as usize,
+        )
+    }
+
+    /// See [`FragmentationReport::should_defragment`] to decide on a defragmentation pass.
+    pub fn fragmentation_report(&self) -> FragmentationReport {
+        FragmentationReport::from_statistics(&self.calculate_statistics())
+    }
+
+    /// Largest free range in the existing blocks of `memory_type`, i.e. the biggest request that
+    /// can be served without allocating new device memory. Alignment is not accounted for; see
+    /// [`Allocator::probe_largest_free_block`] for an exact answer.
+    pub fn largest_free_block(&self, memory_type: u32) -> u64 {
+        self.calculate_statistics()
+            .memory_types
+            .get(memory_type as usize)
+            .map_or(0, |stats| stats.unused_range_size_max)
+    }
+
+    /// Like [`Allocator::largest_free_block`], but binary-searches with `NEVER_ALLOCATE` trial
+    /// allocations aligned to `alignment`. The trials bypass id tracking and tracing. Fails with
+    /// [`Error::InvalidMemoryType`] if `memory_type` is not below the device's memory type count.
+    pub fn probe_largest_free_block(&self, memory_type: u32, alignment: u64) -> Result<u64, Error> {
+        let count = self.raw_memory_properties().memoryTypeCount;
+        if memory_type >= count {
+            return Err(Error::InvalidMemoryType {
+                index: memory_type,
+                count,
+            });
+        }
+
+        let create_info = AllocationCreateInfo::new()
+            .with_creation_flags(AllocationCreateFlags::NEVER_ALLOCATE)
+            .with_memory_type_bits(1 << memory_type);
+
+        let fits = |size: u64| {
+            let requirements = vk::sys::VkMemoryRequirements {
+                size,
+                alignment: alignment.max(1),
+                memoryTypeBits: 1 << memory_type,
+            };
+            let mut allocation = std::ptr::null_mut();
+            let result = unsafe {
+                vmaAllocateMemory(
+                    self.as_raw(),
+                    &requirements,
+                    create_info.as_raw(),
+                    &mut allocation,
+                    std::ptr::null_mut(),
+                )
+            };
+            if result == vk::sys::VK_SUCCESS {
+                unsafe { vmaFreeMemory(self.as_raw(), allocation) };
+            }
+            result == vk::sys::VK_SUCCESS
+        };
+
+        let (mut low, mut high) = (0, self.largest_free_block(memory_type));
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if fits(mid) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Ok(low)
+    }
+
+    pub fn get_heap_budgets(&self) -> Vec<Budget> {
+        let properties = self.raw_memory_properties();
+        let mut budgets: [VmaBudget; vk::sys::VK_MAX_MEMORY_HEAPS as usize] =
+            unsafe { std::mem::zeroed() };
+        unsafe { vmaGetHeapBudgets(self.as_raw(), budgets.as_mut_ptr()) };
+
+        budgets[..properties.memoryHeapCount as usize]
+            .iter()
+            .map(|&budget| budget.into())
+            .collect()
+    }
+
+    /// Builds VMA's JSON dump of the allocator state.
+    pub fn build_stats_string(&self, detailed_map: bool) -> String {
+        let mut raw = std::ptr::null_mut();
+        unsafe {
+            vmaBuildStatsString(self.as_raw(), &mut raw, detailed_map as vk::sys::VkBool32);
+            let string = std::ffi::CStr::from_ptr(raw).to_string_lossy().into_owned();
+            vmaFreeStatsString(self.as_raw(), raw);
+            string
+        }
+    }
+
+    pub fn map_memory(&self, allocation: Allocation) -> Result<NonNull<c_void>, Error> {
+        debug_assert!(!allocation.is_null(), "null allocation");
         let mut data = std::ptr::null_mut();
         let result = unsafe { vmaMapMemory(self.as_raw(), allocation.as_raw(), &mut data) };
         if result != vk::sys::VK_SUCCESS {
-            return Err(());
+            return Err(Error::from_raw(result));
         }
+        map_tracking::track_map(*self, allocation);
         Ok(NonNull::new(data).unwrap())
     }
 
     pub fn unmap_memory(&self, allocation: Allocation) {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        map_tracking::track_unmap(*self, allocation);
         unsafe { vmaUnmapMemory(self.as_raw(), allocation.as_raw()) };
     }
+
+    pub fn flush_allocation(
+        &self,
+        allocation: Allocation,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), Error> {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let result =
+            unsafe { vmaFlushAllocation(self.as_raw(), allocation.as_raw(), offset, size) };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+        Ok(())
+    }
+
+    pub fn invalidate_allocation(
+        &self,
+        allocation: Allocation,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), Error> {
+        debug_assert!(!allocation.is_null(), "null allocation");
+        let result =
+            unsafe { vmaInvalidateAllocation(self.as_raw(), allocation.as_raw(), offset, size) };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+        Ok(())
+    }
+
+    /// Starts tracking maps made through this allocator. Tracking is off by default, and
+    /// untracked maps and unmaps take no lock.
+    pub fn enable_map_tracking(&self) {
+        map_tracking::enable(*self);
+    }
+
+    pub fn disable_map_tracking(&self) {
+        map_tracking::forget_allocator(*self);
+    }
+
+    /// Allocations currently mapped through this allocator, empty unless tracking is enabled.
+    pub fn outstanding_maps(&self) -> Vec<MappedAllocation> {
+        map_tracking::mapped_allocations(*self, Duration::ZERO)
+    }
+
+    /// Allocations that have stayed mapped for at least `older_than`.
+    pub fn long_lived_maps(&self, older_than: Duration) -> Vec<MappedAllocation> {
+        map_tracking::mapped_allocations(*self, older_than)
+    }
+
+    /// Enables tracking and warns about maps held for at least `threshold`, either from
+    /// [`Self::check_long_lived_maps`] while they are still mapped or when they are unmapped.
+    /// Warnings go to `tracing` and the callback set with [`Self::set_map_warning_callback`].
+    pub fn set_long_lived_map_warning(&self, threshold: Option<Duration>) {
+        map_tracking::set_warn_after(*self, threshold);
+    }
+
+    /// Enables tracking and reports unbalanced unmaps, allocations freed while mapped and
+    /// long-lived maps to `callback`.
+    pub fn set_map_warning_callback(&self, callback: Option<MapWarningCallback>) {
+        map_tracking::set_callback(*self, callback);
+    }
+
+    /// Warns about ranges that are still mapped past the long-lived threshold, each once, and
+    /// returns them. Meant to be called periodically, e.g. once per frame.
+    pub fn check_long_lived_maps(&self) -> Vec<MappedAllocation> {
+        map_tracking::check_long_lived(*self)
+    }
+}
+
+/// Implements the `&self` methods of [`Allocator`] on a wrapper by forwarding them to its
+/// private `allocator()`, so the wrapper never hands out the `Copy` handle.
+macro_rules! forward_allocator_methods {
+    ($wrapper:ty) => {
+        forward_allocator_methods! {
+            $wrapper;
+            fn check_no_allocations() -> Result<(), LeakReport>;
+            fn create_buffer(
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Buffer, Allocation), CreateError>;
+            fn create_buffer_unchecked(
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Buffer, Allocation), Error>;
+            fn create_buffer_with_fallback(
+                buffer_create_info: &vk::BufferCreateInfo,
+                primary: &AllocationCreateInfo,
+                fallbacks: &[AllocationCreateInfo],
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Buffer, Allocation, usize), CreateError>;
+            fn create_allocated_buffer(
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<AllocatedBuffer, CreateError>;
+            fn create_buffer_with_address(
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<(vk::Buffer, Allocation, vk::sys::VkDeviceAddress), CreateError>;
+            fn create_buffer_bucketed(
+                buckets: &SizeBuckets,
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Buffer, Allocation), CreateError>;
+            fn allocate_memory_bucketed(
+                buckets: &SizeBuckets,
+                memory_requirements: &vk::MemoryRequirements,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<Allocation, CreateError>;
+            fn destroy_buffer(buffer: vk::Buffer, allocation: Allocation);
+            fn create_image(
+                image_create_info: &vk::ImageCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Image, Allocation), CreateError>;
+            fn create_image_with_fallback(
+                image_create_info: &vk::ImageCreateInfo,
+                primary: &AllocationCreateInfo,
+                fallbacks: &[AllocationCreateInfo],
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<(vk::Image, Allocation, usize), CreateError>;
+            fn create_allocated_image(
+                image_create_info: &vk::ImageCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<AllocatedImage, CreateError>;
+            fn destroy_image(image: vk::Image, allocation: Allocation);
+            fn allocate_memory(
+                memory_requirements: &vk::MemoryRequirements,
+                allocation_create_info: &AllocationCreateInfo,
+                allocation_info: Option<&mut AllocationInfo>,
+            ) -> Result<Allocation, CreateError>;
+            fn free_memory(allocation: Allocation);
+            fn create_aliasing_buffer2(
+                allocation: Allocation,
+                allocation_local_offset: u64,
+                buffer_create_info: &vk::BufferCreateInfo,
+            ) -> Result<vk::Buffer, Error>;
+            fn create_aliasing_image2(
+                allocation: Allocation,
+                allocation_local_offset: u64,
+                image_create_info: &vk::ImageCreateInfo,
+            ) -> Result<vk::Image, Error>;
+            fn set_dedicated_threshold(bytes: u64);
+            fn clear_dedicated_threshold();
+            fn dedicated_threshold() -> Option<u64>;
+            fn create_pool(create_info: &PoolCreateInfo) -> Result<Pool, Error>;
+            fn destroy_pool(pool: Pool);
+            fn find_memory_type_index(
+                memory_type_bits: u32,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<u32, Error>;
+            fn find_memory_type_index_for_buffer_info(
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<u32, Error>;
+            fn find_memory_type_index_for_image_info(
+                image_create_info: &vk::ImageCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<u32, Error>;
+            fn begin_defragmentation(
+                info: &DefragmentationInfo,
+            ) -> Result<DefragmentationContext, Error>;
+            fn end_defragmentation(context: DefragmentationContext) -> DefragmentationStats;
+            fn begin_defragmentation_pass(
+                context: DefragmentationContext,
+            ) -> Result<Option<DefragmentationPass>, Error>;
+            fn end_defragmentation_pass(
+                context: DefragmentationContext,
+                pass: &mut DefragmentationPass,
+            ) -> Result<bool, Error>;
+            fn allocation_version(allocation: Allocation) -> AllocationVersion;
+            fn allocation_changed(allocation: Allocation, version: AllocationVersion) -> bool;
+            fn enable_object_tracking();
+            fn disable_object_tracking();
+            fn allocation_is_valid(allocation: Allocation) -> bool;
+            fn allocation_id(allocation: Allocation) -> Option<u64>;
+            fn pool_id(pool: Pool) -> Option<u64>;
+            fn object_by_id(id: u64) -> Option<TrackedObject>;
+            fn tracked_objects() -> Vec<(u64, TrackedObject)>;
+            fn get_allocation_info(allocation: Allocation) -> AllocationInfo;
+            fn cache_allocation_info(allocation: Allocation) -> AllocationInfoCache;
+            fn get_allocation_info2(allocation: Allocation) -> AllocationInfo2;
+            fn set_allocation_name(allocation: Allocation, name: &std::ffi::CStr);
+            fn allocation_name(allocation: Allocation) -> Option<String>;
+            fn set_allocation_user_data<T>(allocation: Allocation, user_data: *mut T);
+            fn memory_heaps() -> impl Iterator<Item = MemoryHeap>;
+            fn memory_types() -> impl Iterator<Item = MemoryType>;
+            fn calc_preferred_block_size(heap_index: u32) -> u64;
+            fn block_size_warnings() -> Vec<BlockSizeWarning>;
+            fn buffer_image_granularity() -> u64;
+            fn validate_buffer_info(
+                create_info: &vk::BufferCreateInfo,
+                get_physical_device_properties2: vk::sys::PFN_vkGetPhysicalDeviceProperties2,
+            ) -> Result<(), LimitError>;
+            fn validate_image_info(
+                create_info: &vk::ImageCreateInfo,
+                get_physical_device_image_format_properties:
+                    vk::sys::PFN_vkGetPhysicalDeviceImageFormatProperties,
+            ) -> Result<(), LimitError>;
+            fn buffer_memory_requirements(
+                create_info: &vk::BufferCreateInfo,
+                get_device_buffer_memory_requirements:
+                    vk::sys::PFN_vkGetDeviceBufferMemoryRequirements,
+            ) -> Result<vk::MemoryRequirements, Error>;
+            fn image_memory_requirements(
+                create_info: &vk::ImageCreateInfo,
+                get_device_image_memory_requirements:
+                    vk::sys::PFN_vkGetDeviceImageMemoryRequirements,
+            ) -> Result<vk::MemoryRequirements, Error>;
+            fn calculate_statistics() -> TotalStatistics;
+            fn fragmentation_report() -> FragmentationReport;
+            fn largest_free_block(memory_type: u32) -> u64;
+            fn probe_largest_free_block(memory_type: u32, alignment: u64) -> Result<u64, Error>;
+            fn get_heap_budgets() -> Vec<Budget>;
+            fn build_stats_string(detailed_map: bool) -> String;
+            fn map_memory(allocation: Allocation) -> Result<NonNull<c_void>, Error>;
+            fn unmap_memory(allocation: Allocation);
+            fn flush_allocation(allocation: Allocation, offset: u64, size: u64) -> Result<(), Error>;
+            fn invalidate_allocation(
+                allocation: Allocation,
+                offset: u64,
+                size: u64,
+            ) -> Result<(), Error>;
+            fn enable_map_tracking();
+            fn disable_map_tracking();
+            fn outstanding_maps() -> Vec<MappedAllocation>;
+            fn long_lived_maps(older_than: Duration) -> Vec<MappedAllocation>;
+            fn set_long_lived_map_warning(threshold: Option<Duration>);
+            fn set_map_warning_callback(callback: Option<MapWarningCallback>);
+            fn check_long_lived_maps() -> Vec<MappedAllocation>;
+            fn create_buffer_in_quota(
+                quota: &Quota,
+                buffer_create_info: &vk::BufferCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<(vk::Buffer, Allocation), CreateError>;
+            fn destroy_buffer_in_quota(quota: &Quota, buffer: vk::Buffer, allocation: Allocation);
+            fn create_image_in_quota(
+                quota: &Quota,
+                image_create_info: &vk::ImageCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<(vk::Image, Allocation), CreateError>;
+            fn destroy_image_in_quota(quota: &Quota, image: vk::Image, allocation: Allocation);
+            fn allocate_memory_in_quota(
+                quota: &Quota,
+                memory_requirements: &vk::MemoryRequirements,
+                allocation_create_info: &AllocationCreateInfo,
+            ) -> Result<Allocation, CreateError>;
+            fn free_memory_in_quota(quota: &Quota, allocation: Allocation);
+            fn is_api_available(api: future::FutureApi) -> bool;
+            #[cfg(vma_copy_memory)]
+            fn copy_memory_to_allocation(
+                src: &[u8],
+                dst_allocation: Allocation,
+                dst_offset: u64,
+            ) -> Result<(), Error>;
+            #[cfg(vma_copy_memory)]
+            fn copy_allocation_to_memory(
+                src_allocation: Allocation,
+                src_offset: u64,
+                dst: &mut [u8],
+            ) -> Result<(), Error>;
+            #[cfg(all(windows, vma_memory_win32_handle))]
+            fn export_win32_handle(
+                allocation: Allocation,
+                target_process: vk::sys::HANDLE,
+            ) -> Result<vk::sys::HANDLE, Error>;
+            #[cfg(windows)]
+            fn get_memory_win32_handle(
+                allocation: Allocation,
+                get_memory_win32_handle: vk::sys::PFN_vkGetMemoryWin32HandleKHR,
+            ) -> Result<vk::sys::HANDLE, Error>;
+            #[cfg(feature = "texture")]
+            fn create_texture(
+                image_create_info: &vk::ImageCreateInfo,
+                allocation_create_info: &AllocationCreateInfo,
+                data: &[u8],
+                mip_offsets: &[u64],
+                bytes_per_texel: u64,
+            ) -> Result<StagedTexture, Error>;
+            #[cfg(feature = "profiling")]
+            fn set_profiler_sink(sink: impl ProfilerSink + 'static, interval: Duration);
+            #[cfg(feature = "profiling")]
+            fn clear_profiler_sink();
+            #[cfg(feature = "profiling")]
+            fn profiling_snapshot() -> ProfilingSnapshot;
+            #[cfg(feature = "profiling")]
+            fn profile();
+        }
+    };
+    (
+        $wrapper:ty;
+        $(
+            $(#[$meta:meta])*
+            fn $name:ident $(<$generic:ident>)? ($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+        )*
+    ) => {
+        impl $wrapper {
+            $(
+                $(#[$meta])*
+                #[doc = concat!("See [`Allocator::", stringify!($name), "`].")]
+                pub fn $name $(<$generic>)? (&self, $($arg: $ty),*) $(-> $ret)? {
+                    self.allocator().$name($($arg),*)
+                }
+            )*
+        }
+    };
+}
+
+/// An allocator created with `EXTERNALLY_SYNCHRONIZED`. It can move between threads but is not
+/// `Sync`, so it cannot be used from two threads at once. The inner [`Allocator`] handle is never
+/// handed out, as a copy of it would escape that guarantee.
+#[derive(Debug)]
+pub struct AllocatorUnsync {
+    allocator: Allocator,
+    _unsync: PhantomData<Cell<()>>,
+}
+
+impl AllocatorUnsync {
+    pub fn destroy(self) {
+        self.allocator.destroy();
+    }
+
+    fn allocator(&self) -> &Allocator {
+        &self.allocator
+    }
+}
+
+forward_allocator_methods!(AllocatorUnsync);
+
+#[derive(Debug)]
+struct AllocatorOwner(Allocator);
+
+impl Drop for AllocatorOwner {
+    fn drop(&mut self) {
+        self.0.destroy();
+    }
+}
+
+/// A reference-counted [`Allocator`] for subsystems that share one without a single owner.
+/// Resources must be freed before the last clone is dropped.
+#[derive(Debug, Clone)]
+pub struct SharedAllocator {
+    owner: Arc<AllocatorOwner>,
+}
+
+impl SharedAllocator {
+    /// Drops this clone. The allocator is destroyed with the last clone.
+    pub fn destroy(self) {
+        drop(self);
+    }
+
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.owner)
+    }
+
+    fn allocator(&self) -> &Allocator {
+        &self.owner.0
+    }
+}
+
+forward_allocator_methods!(SharedAllocator);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AllocatorCreateInfoError {
+    MissingInstance,
+    MissingPhysicalDevice,
+    MissingDevice,
+    MissingApiVersion,
+}
+
+impl std::fmt::Display for AllocatorCreateInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingInstance => write!(f, "instance handle is not set"),
+            Self::MissingPhysicalDevice => write!(f, "physical device handle is not set"),
+            Self::MissingDevice => write!(f, "device handle is not set"),
+            Self::MissingApiVersion => write!(f, "Vulkan API version is not set"),
+        }
+    }
+}
+
+impl std::error::Error for AllocatorCreateInfoError {}
+
+/// An owned [`AllocatorCreateInfo`] from [`AllocatorCreateInfo::to_owned_snapshot`], safe to
+/// cache and share across threads.
+#[derive(Debug, Clone)]
+pub struct AllocatorCreateInfoSnapshot {
+    raw: VmaAllocatorCreateInfo,
+    allocation_callbacks: Option<vk::sys::VkAllocationCallbacks>,
+    device_memory_callbacks: Option<VmaDeviceMemoryCallbacks>,
+    heap_size_limits: Option<Vec<u64>>,
+    vulkan_functions: Option<VmaVulkanFunctions>,
+    external_memory_handle_types: Option<Vec<vk::sys::VkExternalMemoryHandleTypeFlags>>,
+}
+
+unsafe impl Send for AllocatorCreateInfoSnapshot {}
+unsafe impl Sync for AllocatorCreateInfoSnapshot {}
+
+impl AllocatorCreateInfoSnapshot {
+    /// A create info pointing into this snapshot, valid for as long as the snapshot is.
+    pub fn create_info(&self) -> AllocatorCreateInfo {
+        fn ptr_or_null<T>(value: Option<&T>) -> *const T {
+            value.map_or(std::ptr::null(), |value| value)
+        }
+
+        let mut raw = self.raw;
+        raw.pAllocationCallbacks = ptr_or_null(self.allocation_callbacks.as_ref());
+        raw.pDeviceMemoryCallbacks = ptr_or_null(self.device_memory_callbacks.as_ref());
+        raw.pHeapSizeLimit = self
+            .heap_size_limits
+            .as_ref()
+            .map_or(std::ptr::null(), |limits| limits.as_ptr());
+        raw.pVulkanFunctions = ptr_or_null(self.vulkan_functions.as_ref());
+        raw.pTypeExternalMemoryHandleTypes = self
+            .external_memory_handle_types
+            .as_ref()
+            .map_or(std::ptr::null(), |types| types.as_ptr());
+
+        AllocatorCreateInfo::from_raw(raw)
+    }
 }
 
-vma_struct!(AllocatorCreateInfo, VmaAllocatorCreateInfo);
+vma_struct!(AllocatorCreateInfo, VmaAllocatorCreateInfo, Clone);
 
 impl AllocatorCreateInfo {
+    /// Checks that all required handles and the API version are set.
+    pub fn validate(&self) -> Result<(), AllocatorCreateInfoError> {
+        self.validate_handles()?;
+        if self.inner.vulkanApiVersion == 0 {
+            return Err(AllocatorCreateInfoError::MissingApiVersion);
+        }
+        Ok(())
+    }
+
+    pub fn try_build(self) -> Result<Self, AllocatorCreateInfoError> {
+        self.validate().map(|()| self)
+    }
+
+    /// Deep copies the create info along with the callbacks, functions and arrays it points to.
+    ///
+    /// # Safety
+    /// Every non-null pointer must be valid, with the heap size limits holding
+    /// `memory_heap_count` entries and the external memory handle types `memory_type_count`.
+    pub unsafe fn to_owned_snapshot(
+        &self,
+        memory_heap_count: usize,
+        memory_type_count: usize,
+    ) -> AllocatorCreateInfoSnapshot {
+        unsafe fn copy_array<T: Copy>(ptr: *const T, len: usize) -> Option<Vec<T>> {
+            (!ptr.is_null()).then(|| std::slice::from_raw_parts(ptr, len).to_vec())
+        }
+
+        let raw = &self.inner;
+        AllocatorCreateInfoSnapshot {
+            raw: *raw,
+            allocation_callbacks: raw.pAllocationCallbacks.as_ref().copied(),
+            device_memory_callbacks: raw.pDeviceMemoryCallbacks.as_ref().copied(),
+            heap_size_limits: copy_array(raw.pHeapSizeLimit, memory_heap_count),
+            vulkan_functions: raw.pVulkanFunctions.as_ref().copied(),
+            external_memory_handle_types: copy_array(
+                raw.pTypeExternalMemoryHandleTypes,
+                memory_type_count,
+            ),
+        }
+    }
+
+    /// The checks [`Allocator::create`] always makes. A zero API version is valid to VMA, which
+    /// then assumes Vulkan 1.0.
+    fn validate_handles(&self) -> Result<(), AllocatorCreateInfoError> {
+        if self.inner.instance.is_null() {
+            return Err(AllocatorCreateInfoError::MissingInstance);
+        }
+        if self.inner.physicalDevice.is_null() {
+            return Err(AllocatorCreateInfoError::MissingPhysicalDevice);
+        }
+        if self.inner.device.is_null() {
+            return Err(AllocatorCreateInfoError::MissingDevice);
+        }
+        Ok(())
+    }
+
     pub fn flags(&mut self, flags: AllocatorCreateFlags) -> &mut Self {
         self.inner.flags = flags.bits();
         self
@@ -144,11 +1765,6 @@ impl AllocatorCreateInfo {
     //     self.inner.pHeapSizeLimit = limit;
     //     self
     // }
-    //
-    // pub fn vulkan_functions(&mut self, functions: *const VmaVulkanFunctions) -> &mut Self {
-    //     self.inner.pVulkanFunctions = functions;
-    //     self
-    // }
 
     pub fn with_instance(mut self, instance: vk::Instance) -> Self {
         self.inner.instance = instance.as_raw();
@@ -160,11 +1776,27 @@ impl AllocatorCreateInfo {
         self
     }
 
-    // pub fn type_external_memory_handle_types(
-    //     &mut self,
-    //     types: *const vk::ExternalMemoryHandleTypeFlags,
-    // ) -> &mut Self {
-    //     self.inner.pTypeExternalMemoryHandleTypes = types;
-    //     self
-    // }
+    /// Sets the function table, which [`Allocator::create`] copies.
+    ///
+    /// # Safety
+    /// `functions` is kept by pointer and must outlive every `Allocator::create` call with this
+    /// create info, including clones of it.
+    pub unsafe fn with_vulkan_functions(mut self, functions: &VulkanFunctions) -> Self {
+        self.inner.pVulkanFunctions = functions.as_raw();
+        self
+    }
+
+    /// The external handle types of every block, indexed by memory type. Sized for the maximum
+    /// memory type count so VMA never reads past the end.
+    ///
+    /// # Safety
+    /// `types` is kept by pointer and must outlive every `Allocator::create` call with this create
+    /// info, including clones of it.
+    pub unsafe fn with_type_external_memory_handle_types(
+        mut self,
+        types: &[vk::sys::VkExternalMemoryHandleTypeFlags; vk::sys::VK_MAX_MEMORY_TYPES as usize],
+    ) -> Self {
+        self.inner.pTypeExternalMemoryHandleTypes = types.as_ptr();
+        self
+    }
 }
@@ -0,0 +1,127 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// Heaps up to this size are small to VMA, which caps their blocks at an eighth of the heap.
+pub const SMALL_HEAP_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// The block size of large heaps when `preferredLargeHeapBlockSize` is left at 0.
+pub const DEFAULT_LARGE_HEAP_BLOCK_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The preferred (maximum) block size in a heap of `heap_size` bytes, reproducing
+/// `VmaAllocator_T::CalcPreferredBlockSize`. Default pools may start with blocks up to 8 times
+/// smaller and grow towards it. A `preferred_large_heap_block_size` of 0 means the default.
+pub const fn preferred_block_size(heap_size: u64, preferred_large_heap_block_size: u64) -> u64 {
+    let large = match preferred_large_heap_block_size {
+        0 => DEFAULT_LARGE_HEAP_BLOCK_SIZE,
+        size => size,
+    };
+    let size = match heap_size <= SMALL_HEAP_MAX_SIZE {
+        true => heap_size / 8,
+        false => large,
+    };
+    match size.checked_next_multiple_of(32) {
+        Some(size) => size,
+        None => size,
+    }
+}
+
+/// A heap smaller than its preferred block size, so VMA can never grow blocks to that size and
+/// large allocations fall back to dedicated memory or fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSizeWarning {
+    pub heap_index: u32,
+    pub heap_size: u64,
+    pub block_size: u64,
+}
+
+impl std::fmt::Display for BlockSizeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "preferred block size {} exceeds the {} of heap {}",
+            ByteSize(self.block_size),
+            ByteSize(self.heap_size),
+            self.heap_index
+        )
+    }
+}
+
+static LARGE_HEAP_BLOCK_SIZES: Mutex<BTreeMap<Allocator, u64>> = Mutex::new(BTreeMap::new());
+
+fn large_heap_block_sizes() -> MutexGuard<'static, BTreeMap<Allocator, u64>> {
+    LARGE_HEAP_BLOCK_SIZES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Records the `preferredLargeHeapBlockSize` the allocator was created with, which VMA does not
+/// report back.
+pub(crate) fn set_large_heap_block_size(allocator: Allocator, size: u64) {
+    match size {
+        0 => large_heap_block_sizes().remove(&allocator),
+        size => large_heap_block_sizes().insert(allocator, size),
+    };
+}
+
+pub(crate) fn large_heap_block_size(allocator: Allocator) -> u64 {
+    large_heap_block_sizes()
+        .get(&allocator)
+        .copied()
+        .unwrap_or(DEFAULT_LARGE_HEAP_BLOCK_SIZE)
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    large_heap_block_sizes().remove(&allocator);
+}
+
+pub(crate) fn host_bytes() -> usize {
+    large_heap_block_sizes().len() * std::mem::size_of::<(Allocator, u64)>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_heaps_use_an_eighth() {
+        assert_eq!(
+            preferred_block_size(SMALL_HEAP_MAX_SIZE, 0),
+            SMALL_HEAP_MAX_SIZE / 8
+        );
+        assert_eq!(preferred_block_size(256 * 1024 * 1024, 0), 32 * 1024 * 1024);
+        // The large heap setting does not apply to small heaps.
+        assert_eq!(preferred_block_size(8 * 1024, 1024), 1024);
+    }
+
+    #[test]
+    fn large_heaps_use_the_preferred_size() {
+        let heap = SMALL_HEAP_MAX_SIZE + 1;
+        assert_eq!(preferred_block_size(heap, 0), DEFAULT_LARGE_HEAP_BLOCK_SIZE);
+        assert_eq!(
+            preferred_block_size(heap, 64 * 1024 * 1024),
+            64 * 1024 * 1024
+        );
+        assert_eq!(
+            preferred_block_size(u64::MAX, 0),
+            DEFAULT_LARGE_HEAP_BLOCK_SIZE
+        );
+    }
+
+    #[test]
+    fn sizes_round_up_to_32() {
+        assert_eq!(preferred_block_size(0, 0), 0);
+        assert_eq!(preferred_block_size(8, 0), 32);
+        assert_eq!(preferred_block_size(8 * 33, 0), 64);
+        assert_eq!(preferred_block_size(u64::MAX, 100), 128);
+    }
+
+    #[test]
+    fn rounding_does_not_overflow() {
+        assert_eq!(preferred_block_size(u64::MAX, u64::MAX), u64::MAX);
+        assert_eq!(preferred_block_size(u64::MAX, u64::MAX - 32), u64::MAX - 31);
+    }
+}
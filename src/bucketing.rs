@@ -0,0 +1,186 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketPolicy {
+    /// Sizes of at least `min_size` are rounded up to the next power of two.
+    PowerOfTwo { min_size: u64 },
+    /// Sizes of at least `min_size` are rounded up to a multiple of `granularity`.
+    Granularity { min_size: u64, granularity: u64 },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BucketStats {
+    pub requested_bytes: u64,
+    pub rounded_bytes: u64,
+}
+
+impl BucketStats {
+    pub const fn overhead_bytes(&self) -> u64 {
+        self.rounded_bytes.saturating_sub(self.requested_bytes)
+    }
+}
+
+/// Opt-in rounding of allocation sizes to bucket boundaries to improve block reuse.
+///
+/// The totals reported by [`SizeBuckets::stats`] accumulate over every size
+/// rounded through this policy.
+#[derive(Debug)]
+pub struct SizeBuckets {
+    policy: BucketPolicy,
+    requested_bytes: AtomicU64,
+    rounded_bytes: AtomicU64,
+}
+
+impl SizeBuckets {
+    pub const fn new(policy: BucketPolicy) -> Self {
+        Self {
+            policy,
+            requested_bytes: AtomicU64::new(0),
+            rounded_bytes: AtomicU64::new(0),
+        }
+    }
+
+    pub const fn policy(&self) -> BucketPolicy {
+        self.policy
+    }
+
+    /// Zero stays zero, and sizes whose bucket would overflow are left as they are.
+    pub const fn bucket_size(&self, size: u64) -> u64 {
+        if size == 0 {
+            return 0;
+        }
+
+        match self.policy {
+            BucketPolicy::PowerOfTwo { min_size } if size >= min_size => {
+                match size.checked_next_power_of_two() {
+                    Some(rounded) => rounded,
+                    None => size,
+                }
+            }
+            BucketPolicy::Granularity {
+                min_size,
+                granularity,
+            } if size >= min_size && granularity != 0 => {
+                match size.checked_next_multiple_of(granularity) {
+                    Some(rounded) => rounded,
+                    None => size,
+                }
+            }
+            _ => size,
+        }
+    }
+
+    pub fn round(&self, size: u64) -> u64 {
+        let rounded = self.bucket_size(size);
+        saturating_add(&self.requested_bytes, size);
+        saturating_add(&self.rounded_bytes, rounded);
+        rounded
+    }
+
+    pub fn stats(&self) -> BucketStats {
+        BucketStats {
+            requested_bytes: self.requested_bytes.load(Ordering::Relaxed),
+            rounded_bytes: self.rounded_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.requested_bytes.store(0, Ordering::Relaxed);
+        self.rounded_bytes.store(0, Ordering::Relaxed);
+    }
+}
+
+fn saturating_add(total: &AtomicU64, bytes: u64) {
+    let _ = total.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |total| {
+        Some(total.saturating_add(bytes))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POWER_OF_TWO: BucketPolicy = BucketPolicy::PowerOfTwo { min_size: 1024 };
+    const GRANULARITY: BucketPolicy = BucketPolicy::Granularity {
+        min_size: 1024,
+        granularity: 4096,
+    };
+
+    #[test]
+    fn zero_size_stays_zero() {
+        for policy in [
+            POWER_OF_TWO,
+            GRANULARITY,
+            BucketPolicy::PowerOfTwo { min_size: 0 },
+            BucketPolicy::Granularity {
+                min_size: 0,
+                granularity: 256,
+            },
+        ] {
+            assert_eq!(SizeBuckets::new(policy).bucket_size(0), 0);
+        }
+    }
+
+    #[test]
+    fn power_of_two_boundaries() {
+        let buckets = SizeBuckets::new(POWER_OF_TWO);
+        assert_eq!(buckets.bucket_size(1000), 1000);
+        assert_eq!(buckets.bucket_size(1023), 1023);
+        assert_eq!(buckets.bucket_size(1024), 1024);
+        assert_eq!(buckets.bucket_size(1025), 2048);
+        assert_eq!(buckets.bucket_size(1 << 63), 1 << 63);
+        assert_eq!(buckets.bucket_size((1 << 63) + 1), (1 << 63) + 1);
+        assert_eq!(buckets.bucket_size(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn granularity_boundaries() {
+        let buckets = SizeBuckets::new(GRANULARITY);
+        assert_eq!(buckets.bucket_size(1023), 1023);
+        assert_eq!(buckets.bucket_size(1024), 4096);
+        assert_eq!(buckets.bucket_size(4096), 4096);
+        assert_eq!(buckets.bucket_size(4097), 8192);
+        assert_eq!(buckets.bucket_size(u64::MAX), u64::MAX);
+
+        let unaligned = SizeBuckets::new(BucketPolicy::Granularity {
+            min_size: 0,
+            granularity: 0,
+        });
+        assert_eq!(unaligned.bucket_size(1000), 1000);
+    }
+
+    #[test]
+    fn stats_accumulate_and_reset() {
+        let buckets = SizeBuckets::new(POWER_OF_TWO);
+        assert_eq!(buckets.stats(), BucketStats::default());
+        assert_eq!(buckets.stats().overhead_bytes(), 0);
+
+        buckets.round(1025);
+        buckets.round(100);
+        assert_eq!(
+            buckets.stats(),
+            BucketStats {
+                requested_bytes: 1125,
+                rounded_bytes: 2148,
+            }
+        );
+        assert_eq!(buckets.stats().overhead_bytes(), 1023);
+
+        buckets.reset_stats();
+        assert_eq!(buckets.stats(), BucketStats::default());
+    }
+
+    #[test]
+    fn stats_saturate() {
+        let buckets = SizeBuckets::new(POWER_OF_TWO);
+        buckets.round(u64::MAX);
+        buckets.round(1025);
+        let stats = buckets.stats();
+        assert_eq!(stats.requested_bytes, u64::MAX);
+        assert_eq!(stats.rounded_bytes, u64::MAX);
+        assert_eq!(stats.overhead_bytes(), 0);
+    }
+}
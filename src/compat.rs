@@ -0,0 +1,41 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+//! Stand-ins for the VMA 2.x "lost allocation" API, removed in VMA 3. They keep migrating code
+//! compiling, and each deprecation note names the replacement. Allocations can no longer become
+//! lost, so none of these do anything.
+
+use crate::*;
+
+#[deprecated(note = "allocations can no longer become lost")]
+pub const FRAME_INDEX_LOST: u32 = u32::MAX;
+
+impl AllocationCreateFlags {
+    #[deprecated(note = "allocations can no longer become lost; remove the flag")]
+    pub const CAN_BECOME_LOST: Self = Self::empty();
+
+    #[deprecated(note = "allocations can no longer become lost; remove the flag")]
+    pub const CAN_MAKE_OTHER_LOST: Self = Self::empty();
+}
+
+impl PoolCreateInfo {
+    #[deprecated(note = "frame in use counts went away with lost allocations; remove the call")]
+    pub fn with_frame_in_use_count(self, _count: u32) -> Self {
+        self
+    }
+}
+
+impl Allocator {
+    #[deprecated(note = "use `Allocator::allocation_is_valid`")]
+    pub fn touch_allocation(&self, allocation: Allocation) -> bool {
+        self.allocation_is_valid(allocation)
+    }
+
+    #[deprecated(note = "frame indices went away with lost allocations; remove the call")]
+    pub fn set_current_frame_index(&self, _frame_index: u32) {}
+
+    #[deprecated(note = "allocations can no longer become lost; free them explicitly")]
+    pub fn make_pool_allocations_lost(&self, _pool: Pool) -> usize {
+        0
+    }
+}
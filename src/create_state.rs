@@ -0,0 +1,69 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, MutexGuard};
+use vma_sys::*;
+
+/// What an allocator was created with that VMA does not report back.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CreateState {
+    pub flags: AllocatorCreateFlags,
+    /// Loaded through the function table's `vkGetDeviceProcAddr` when the allocator is created
+    /// with `BUFFER_DEVICE_ADDRESS`.
+    pub get_buffer_device_address: vk::sys::PFN_vkGetBufferDeviceAddress,
+}
+
+static CREATE_STATES: Mutex<BTreeMap<Allocator, CreateState>> = Mutex::new(BTreeMap::new());
+
+fn create_states() -> MutexGuard<'static, BTreeMap<Allocator, CreateState>> {
+    CREATE_STATES.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn record(allocator: Allocator, create_info: &AllocatorCreateInfo) {
+    let raw = create_info.as_raw();
+    let flags = AllocatorCreateFlags::from_bits_retain(raw.flags);
+
+    let get_buffer_device_address =
+        match flags.contains(AllocatorCreateFlags::BUFFER_DEVICE_ADDRESS) {
+            true => unsafe { load_buffer_device_address(raw) },
+            false => None,
+        };
+
+    let state = CreateState {
+        flags,
+        get_buffer_device_address,
+    };
+    create_states().insert(allocator, state);
+}
+
+/// Core in 1.2, `VK_KHR_buffer_device_address` before that.
+unsafe fn load_buffer_device_address(
+    raw: &VmaAllocatorCreateInfo,
+) -> vk::sys::PFN_vkGetBufferDeviceAddress {
+    let functions = raw.pVulkanFunctions.as_ref()?;
+    let get_device_proc_addr: vk::sys::PFN_vkGetDeviceProcAddr =
+        std::mem::transmute_copy(&functions.vkGetDeviceProcAddr);
+    let get_device_proc_addr = get_device_proc_addr?;
+
+    let function = [c"vkGetBufferDeviceAddress", c"vkGetBufferDeviceAddressKHR"]
+        .iter()
+        .find_map(|name| get_device_proc_addr(raw.device, name.as_ptr()));
+    std::mem::transmute::<vk::sys::PFN_vkVoidFunction, vk::sys::PFN_vkGetBufferDeviceAddress>(
+        function,
+    )
+}
+
+/// `None` for allocators adopted through `from_raw` rather than created by the crate.
+pub(crate) fn get(allocator: Allocator) -> Option<CreateState> {
+    create_states().get(&allocator).copied()
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    create_states().remove(&allocator);
+}
+
+pub(crate) fn host_bytes() -> usize {
+    create_states().len() * std::mem::size_of::<(Allocator, CreateState)>()
+}
@@ -0,0 +1,60 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+static THRESHOLDS: Mutex<BTreeMap<Allocator, u64>> = Mutex::new(BTreeMap::new());
+
+/// Entries in `THRESHOLDS`, so allocators without a threshold skip the lock.
+static LEN: AtomicUsize = AtomicUsize::new(0);
+
+fn thresholds() -> MutexGuard<'static, BTreeMap<Allocator, u64>> {
+    THRESHOLDS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn set_threshold(allocator: Allocator, threshold: Option<u64>) {
+    if threshold.is_none() && LEN.load(Ordering::Relaxed) == 0 {
+        return;
+    }
+
+    let mut thresholds = thresholds();
+    match threshold {
+        Some(threshold) => thresholds.insert(allocator, threshold),
+        None => thresholds.remove(&allocator),
+    };
+    LEN.store(thresholds.len(), Ordering::Relaxed);
+}
+
+pub(crate) fn threshold(allocator: Allocator) -> Option<u64> {
+    if LEN.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    thresholds().get(&allocator).copied()
+}
+
+pub(crate) fn host_bytes() -> usize {
+    thresholds().len() * std::mem::size_of::<(Allocator, u64)>()
+}
+
+/// Applies the allocator's dedicated threshold to `allocation_create_info` for a request of `size` bytes.
+///
+/// Returns `None` when no threshold is set, in which case the caller's flags are used as-is.
+pub(crate) fn apply(
+    allocator: Allocator,
+    size: u64,
+    allocation_create_info: &AllocationCreateInfo,
+) -> Option<AllocationCreateInfo> {
+    let threshold = threshold(allocator)?;
+
+    let mut raw = *allocation_create_info.as_raw();
+    // The flag is only ever added, so an explicit DEDICATED_MEMORY below the threshold is kept. A
+    // dedicated allocation always allocates, so NEVER_ALLOCATE wins over the threshold.
+    if size > threshold && raw.flags & AllocationCreateFlags::NEVER_ALLOCATE.bits() == 0 {
+        raw.flags |= AllocationCreateFlags::DEDICATED_MEMORY.bits();
+    }
+
+    Some(AllocationCreateInfo::from_raw(raw))
+}
@@ -0,0 +1,109 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::macros::*;
+use crate::*;
+use vma_sys::*;
+
+vma_handle!(DefragmentationContext, VmaDefragmentationContext);
+
+vma_struct!(DefragmentationInfo, VmaDefragmentationInfo, Clone);
+
+impl DefragmentationInfo {
+    /// Limits defragmentation to `pool`, leaving the default pools and other custom pools alone.
+    pub fn for_pool(pool: Pool) -> Self {
+        Self::new().with_pool(pool)
+    }
+
+    pub fn with_flags(mut self, flags: DefragmentationFlags) -> Self {
+        self.inner.flags = flags.bits();
+        self
+    }
+
+    pub fn with_pool(mut self, pool: Pool) -> Self {
+        self.inner.pool = pool.as_raw();
+        self
+    }
+
+    /// Caps the bytes moved in one pass, 0 meaning no limit.
+    pub fn with_max_bytes_per_pass(mut self, bytes: u64) -> Self {
+        self.inner.maxBytesPerPass = bytes;
+        self
+    }
+
+    /// Caps the allocations moved in one pass, 0 meaning no limit.
+    pub fn with_max_allocations_per_pass(mut self, count: u32) -> Self {
+        self.inner.maxAllocationsPerPass = count;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DefragmentationStats {
+    pub bytes_moved: u64,
+    pub bytes_freed: u64,
+    pub allocations_moved: u32,
+    pub device_memory_blocks_freed: u32,
+}
+
+impl From<VmaDefragmentationStats> for DefragmentationStats {
+    fn from(raw: VmaDefragmentationStats) -> Self {
+        Self {
+            bytes_moved: raw.bytesMoved,
+            bytes_freed: raw.bytesFreed,
+            allocations_moved: raw.allocationsMoved,
+            device_memory_blocks_freed: raw.deviceMemoryBlocksFreed,
+        }
+    }
+}
+
+vma_struct!(DefragmentationMove, VmaDefragmentationMove);
+
+impl DefragmentationMove {
+    pub const fn operation(&self) -> DefragmentationMoveOperation {
+        DefragmentationMoveOperation::from_raw(self.inner.operation)
+    }
+
+    pub fn set_operation(&mut self, operation: DefragmentationMoveOperation) {
+        self.inner.operation = operation.as_raw();
+    }
+
+    pub const fn src_allocation(&self) -> Allocation {
+        unsafe { Allocation::from_raw(self.inner.srcAllocation) }
+    }
+
+    pub const fn dst_tmp_allocation(&self) -> Allocation {
+        unsafe { Allocation::from_raw(self.inner.dstTmpAllocation) }
+    }
+}
+
+// The moves VMA wants performed in one defragmentation pass. For each COPY move, the caller creates
+// a new resource bound to dst_tmp_allocation and copies the data over before ending the pass.
+vma_struct!(DefragmentationPass, VmaDefragmentationPassMoveInfo, unsafe);
+
+impl DefragmentationPass {
+    pub fn moves(&self) -> &[DefragmentationMove] {
+        if self.inner.moveCount == 0 {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(
+                self.inner.pMoves as *const DefragmentationMove,
+                self.inner.moveCount as usize,
+            )
+        }
+    }
+
+    pub fn moves_mut(&mut self) -> &mut [DefragmentationMove] {
+        if self.inner.moveCount == 0 {
+            return &mut [];
+        }
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.inner.pMoves as *mut DefragmentationMove,
+                self.inner.moveCount as usize,
+            )
+        }
+    }
+}
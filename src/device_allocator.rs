@@ -0,0 +1,99 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+/// The resource and memory operations engine code needs, so it can be generic over [`Allocator`]
+/// and test doubles such as `MockAllocator` (behind the `testing` feature).
+pub trait DeviceAllocator {
+    fn create_buffer(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Buffer, Allocation), Error>;
+
+    fn destroy_buffer(&self, buffer: vk::Buffer, allocation: Allocation);
+
+    fn create_image(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Image, Allocation), Error>;
+
+    fn destroy_image(&self, image: vk::Image, allocation: Allocation);
+
+    fn allocate_memory(
+        &self,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Allocation, Error>;
+
+    fn free_memory(&self, allocation: Allocation);
+
+    fn map_memory(&self, allocation: Allocation) -> Result<NonNull<c_void>, Error>;
+
+    fn unmap_memory(&self, allocation: Allocation);
+
+    fn flush_allocation(&self, allocation: Allocation, offset: u64, size: u64)
+        -> Result<(), Error>;
+}
+
+impl DeviceAllocator for Allocator {
+    fn create_buffer(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Buffer, Allocation), Error> {
+        Allocator::create_buffer(self, buffer_create_info, allocation_create_info, None)
+            .map_err(Error::from)
+    }
+
+    fn destroy_buffer(&self, buffer: vk::Buffer, allocation: Allocation) {
+        Allocator::destroy_buffer(self, buffer, allocation)
+    }
+
+    fn create_image(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Image, Allocation), Error> {
+        Allocator::create_image(self, image_create_info, allocation_create_info, None)
+            .map_err(Error::from)
+    }
+
+    fn destroy_image(&self, image: vk::Image, allocation: Allocation) {
+        Allocator::destroy_image(self, image, allocation)
+    }
+
+    fn allocate_memory(
+        &self,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Allocation, Error> {
+        Allocator::allocate_memory(self, memory_requirements, allocation_create_info, None)
+            .map_err(Error::from)
+    }
+
+    fn free_memory(&self, allocation: Allocation) {
+        Allocator::free_memory(self, allocation)
+    }
+
+    fn map_memory(&self, allocation: Allocation) -> Result<NonNull<c_void>, Error> {
+        Allocator::map_memory(self, allocation)
+    }
+
+    fn unmap_memory(&self, allocation: Allocation) {
+        Allocator::unmap_memory(self, allocation)
+    }
+
+    fn flush_allocation(
+        &self,
+        allocation: Allocation,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), Error> {
+        Allocator::flush_allocation(self, allocation, offset, size)
+    }
+}
@@ -3,8 +3,21 @@
 
 #![allow(nonstandard_style)]
 #![allow(unused)]
+#![allow(deprecated)]
 
 use bitflags::bitflags;
 use vma_sys::*;
 
 include!(concat!(env!("OUT_DIR"), "/enums.rs"));
+
+/// A raw value that does not match any variant of the enum it was converted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidEnumValue(pub i32);
+
+impl std::fmt::Display for InvalidEnumValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid enum value {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidEnumValue {}
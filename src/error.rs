@@ -0,0 +1,285 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    OutOfHostMemory,
+    OutOfDeviceMemory,
+    InitializationFailed,
+    MemoryMapFailed,
+    FeatureNotPresent,
+    TooManyObjects,
+    /// The pool has enough free space in total, but no single range is large enough.
+    FragmentedPool,
+    /// The pool has reached its block limit.
+    OutOfPoolMemory,
+    /// `Allocator::create` was given a create info with missing handles.
+    InvalidCreateInfo(AllocatorCreateInfoError),
+    /// Rejected before reaching the driver by the `validation` feature.
+    LimitExceeded(LimitError),
+    /// The block or pool algorithm does not support the operation.
+    UnsupportedAlgorithm,
+    /// The buffer was created without usage flags the operation needs.
+    MissingBufferUsage(vk::sys::VkBufferUsageFlags),
+    /// `Allocator::create_texture` was given `offsets` mip offsets for an image with `levels`
+    /// mip levels.
+    MipLevelMismatch {
+        levels: u32,
+        offsets: usize,
+    },
+    /// `AllocatorRegistry::create` was given a device that already has an allocator.
+    DeviceAlreadyRegistered,
+    /// `offset..offset + len` does not fit in `size`, in the units of the call, e.g. elements for
+    /// [`MappedBuffer::write`].
+    OutOfBounds {
+        offset: u64,
+        len: u64,
+        size: u64,
+    },
+    /// The named Vulkan or VMA function was not loaded, or the extension or allocator flag it
+    /// needs is not enabled.
+    MissingFunction(&'static str),
+    /// A [`TransientResource`] whose `last_pass` comes before its `first_pass`.
+    InvalidPassRange {
+        first_pass: u32,
+        last_pass: u32,
+    },
+    /// A memory type index that is not below the device's memory type count.
+    InvalidMemoryType {
+        index: u32,
+        count: u32,
+    },
+    /// An AUTO memory usage was given without a buffer or image to pick the memory type for, e.g.
+    /// by a [`PoolBuilder`] with neither `with_buffer_info` nor `with_image_info`.
+    MissingResourceInfo,
+    /// Refused by a [`Quota`] that has less than `requested` bytes left.
+    QuotaExceeded {
+        requested: u64,
+        available: u64,
+    },
+    Other(vk::sys::VkResult),
+}
+
+impl Error {
+    pub const fn from_raw(result: vk::sys::VkResult) -> Self {
+        match result {
+            vk::sys::VK_ERROR_OUT_OF_HOST_MEMORY => Self::OutOfHostMemory,
+            vk::sys::VK_ERROR_OUT_OF_DEVICE_MEMORY => Self::OutOfDeviceMemory,
+            vk::sys::VK_ERROR_INITIALIZATION_FAILED => Self::InitializationFailed,
+            vk::sys::VK_ERROR_MEMORY_MAP_FAILED => Self::MemoryMapFailed,
+            vk::sys::VK_ERROR_FEATURE_NOT_PRESENT => Self::FeatureNotPresent,
+            vk::sys::VK_ERROR_TOO_MANY_OBJECTS => Self::TooManyObjects,
+            vk::sys::VK_ERROR_FRAGMENTED_POOL | vk::sys::VK_ERROR_FRAGMENTATION => {
+                Self::FragmentedPool
+            }
+            vk::sys::VK_ERROR_OUT_OF_POOL_MEMORY => Self::OutOfPoolMemory,
+            result => Self::Other(result),
+        }
+    }
+
+    pub const fn as_raw(&self) -> vk::sys::VkResult {
+        match self {
+            Self::OutOfHostMemory => vk::sys::VK_ERROR_OUT_OF_HOST_MEMORY,
+            Self::OutOfDeviceMemory => vk::sys::VK_ERROR_OUT_OF_DEVICE_MEMORY,
+            Self::InitializationFailed => vk::sys::VK_ERROR_INITIALIZATION_FAILED,
+            Self::MemoryMapFailed => vk::sys::VK_ERROR_MEMORY_MAP_FAILED,
+            Self::FeatureNotPresent => vk::sys::VK_ERROR_FEATURE_NOT_PRESENT,
+            Self::TooManyObjects => vk::sys::VK_ERROR_TOO_MANY_OBJECTS,
+            Self::FragmentedPool => vk::sys::VK_ERROR_FRAGMENTED_POOL,
+            Self::OutOfPoolMemory => vk::sys::VK_ERROR_OUT_OF_POOL_MEMORY,
+            Self::InvalidCreateInfo(_) => vk::sys::VK_ERROR_INITIALIZATION_FAILED,
+            Self::LimitExceeded(_) => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::UnsupportedAlgorithm => vk::sys::VK_ERROR_FEATURE_NOT_PRESENT,
+            Self::MissingBufferUsage(_) => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::OutOfBounds { .. } => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::DeviceAlreadyRegistered => vk::sys::VK_ERROR_INITIALIZATION_FAILED,
+            Self::MipLevelMismatch { .. } => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::MissingFunction(_) => vk::sys::VK_ERROR_EXTENSION_NOT_PRESENT,
+            Self::InvalidPassRange { .. } => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::InvalidMemoryType { .. } => vk::sys::VK_ERROR_VALIDATION_FAILED_EXT,
+            Self::MissingResourceInfo => vk::sys::VK_ERROR_FEATURE_NOT_PRESENT,
+            Self::QuotaExceeded { .. } => vk::sys::VK_ERROR_OUT_OF_DEVICE_MEMORY,
+            Self::Other(result) => *result,
+        }
+    }
+
+    /// Whether the request ran out of device memory (or budget, with `WITHIN_BUDGET`) and may
+    /// succeed with a different heap, memory type or pool.
+    pub const fn is_out_of_memory(&self) -> bool {
+        matches!(
+            self,
+            Self::OutOfDeviceMemory
+                | Self::FeatureNotPresent
+                | Self::TooManyObjects
+                | Self::FragmentedPool
+                | Self::OutOfPoolMemory
+        )
+    }
+
+    /// Whether compacting the pool (see [`Pool::compact`]) may let the request succeed.
+    pub const fn is_fragmentation(&self) -> bool {
+        matches!(self, Self::FragmentedPool)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfHostMemory => write!(f, "out of host memory"),
+            Self::OutOfDeviceMemory => write!(f, "out of device memory"),
+            Self::InitializationFailed => write!(f, "initialization failed"),
+            Self::MemoryMapFailed => write!(f, "memory map failed"),
+            Self::FeatureNotPresent => write!(f, "no memory type satisfies the requirements"),
+            Self::TooManyObjects => write!(f, "too many device memory allocations"),
+            Self::FragmentedPool => write!(f, "pool is too fragmented for the request"),
+            Self::OutOfPoolMemory => write!(f, "pool has no room for the request"),
+            Self::InvalidCreateInfo(err) => write!(f, "invalid allocator create info: {}", err),
+            Self::LimitExceeded(err) => write!(f, "{}", err),
+            Self::UnsupportedAlgorithm => write!(f, "not supported by the block's algorithm"),
+            Self::MissingBufferUsage(usage) => write!(f, "buffer usage is missing {:#x}", usage),
+            Self::DeviceAlreadyRegistered => write!(f, "device already has an allocator"),
+            Self::MipLevelMismatch { levels, offsets } => {
+                write!(f, "{} mip offsets given for {} mip levels", offsets, levels)
+            }
+            Self::OutOfBounds { offset, len, size } => write!(
+                f,
+                "range {}..{} is out of bounds of {}",
+                offset,
+                offset.saturating_add(*len),
+                size
+            ),
+            Self::MissingFunction(name) => write!(f, "{} is not available", name),
+            Self::InvalidPassRange {
+                first_pass,
+                last_pass,
+            } => write!(
+                f,
+                "resource ends at pass {} before it starts at pass {}",
+                last_pass, first_pass
+            ),
+            Self::InvalidMemoryType { index, count } => write!(
+                f,
+                "memory type {} is out of range of the {} memory types",
+                index, count
+            ),
+            Self::MissingResourceInfo => {
+                write!(f, "AUTO memory usage needs buffer or image info")
+            }
+            Self::QuotaExceeded {
+                requested,
+                available,
+            } => write!(
+                f,
+                "quota exceeded: {} requested, {} available",
+                ByteSize(*requested),
+                ByteSize(*available)
+            ),
+            Self::Other(result) => write!(f, "VkResult {}", result),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// What was being created when a [`CreateError`] occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateRequest {
+    Buffer {
+        size: u64,
+        usage: vk::sys::VkBufferUsageFlags,
+    },
+    Image {
+        extent: [u32; 3],
+        format: vk::sys::VkFormat,
+        usage: vk::sys::VkImageUsageFlags,
+    },
+    Memory {
+        size: u64,
+        alignment: u64,
+        memory_type_bits: u32,
+    },
+}
+
+/// A failed buffer, image or memory creation along with the request that caused it, for crash
+/// logs. Converts into the plain [`Error`] with `?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreateError {
+    pub error: Error,
+    pub request: CreateRequest,
+    pub memory_usage: MemoryUsage,
+    /// The `memoryTypeBits` of the allocation create info, 0 meaning any type.
+    pub memory_type_bits: u32,
+}
+
+impl CreateError {
+    pub(crate) const fn new(
+        error: Error,
+        request: CreateRequest,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Self {
+        let raw = allocation_create_info.as_raw();
+        Self {
+            error,
+            request,
+            memory_usage: MemoryUsage::from_raw(raw.usage),
+            memory_type_bits: raw.memoryTypeBits,
+        }
+    }
+
+    pub const fn as_raw(&self) -> vk::sys::VkResult {
+        self.error.as_raw()
+    }
+}
+
+impl From<CreateError> for Error {
+    fn from(err: CreateError) -> Self {
+        err.error
+    }
+}
+
+impl std::fmt::Display for CreateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.request {
+            CreateRequest::Buffer { size, usage } => write!(
+                f,
+                "failed to create buffer of {} bytes (usage {:#x})",
+                size, usage
+            )?,
+            CreateRequest::Image {
+                extent,
+                format,
+                usage,
+            } => write!(
+                f,
+                "failed to create {}x{}x{} image (format {}, usage {:#x})",
+                extent[0], extent[1], extent[2], format, usage
+            )?,
+            CreateRequest::Memory {
+                size,
+                alignment,
+                memory_type_bits,
+            } => write!(
+                f,
+                "failed to allocate {} bytes (alignment {}, required types {:#x})",
+                size, alignment, memory_type_bits
+            )?,
+        }
+
+        write!(
+            f,
+            " with {} memory and types {:#x}: {} ({})",
+            self.memory_usage,
+            self.memory_type_bits,
+            self.error,
+            self.error.as_raw()
+        )
+    }
+}
+
+impl std::error::Error for CreateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
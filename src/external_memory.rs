@@ -0,0 +1,105 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+//! Typed pNext structs for exportable resources. Create infos passed to the allocator keep their
+//! pNext chains, so these are prepended to whatever chain is already there.
+
+use crate::*;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// A create info with a borrowed struct prepended to its pNext chain.
+pub struct Chained<'a, T> {
+    info: T,
+    _next: PhantomData<&'a mut ()>,
+}
+
+impl<'a, T> Deref for Chained<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.info
+    }
+}
+
+pub struct ExternalMemoryBufferCreateInfo {
+    inner: vk::sys::VkExternalMemoryBufferCreateInfo,
+}
+
+impl ExternalMemoryBufferCreateInfo {
+    pub const fn new(handle_types: vk::sys::VkExternalMemoryHandleTypeFlags) -> Self {
+        Self {
+            inner: vk::sys::VkExternalMemoryBufferCreateInfo {
+                sType: vk::sys::VK_STRUCTURE_TYPE_EXTERNAL_MEMORY_BUFFER_CREATE_INFO,
+                pNext: std::ptr::null(),
+                handleTypes: handle_types,
+            },
+        }
+    }
+
+    pub fn chain<'a>(
+        &'a mut self,
+        create_info: &vk::BufferCreateInfo,
+    ) -> Chained<'a, vk::BufferCreateInfo> {
+        let mut raw = *create_info.as_raw();
+        self.inner.pNext = raw.pNext;
+        raw.pNext = &self.inner as *const _ as *const c_void;
+
+        Chained {
+            info: vk::BufferCreateInfo::from_raw(raw),
+            _next: PhantomData,
+        }
+    }
+}
+
+pub struct ExternalMemoryImageCreateInfo {
+    inner: vk::sys::VkExternalMemoryImageCreateInfo,
+}
+
+impl ExternalMemoryImageCreateInfo {
+    pub const fn new(handle_types: vk::sys::VkExternalMemoryHandleTypeFlags) -> Self {
+        Self {
+            inner: vk::sys::VkExternalMemoryImageCreateInfo {
+                sType: vk::sys::VK_STRUCTURE_TYPE_EXTERNAL_MEMORY_IMAGE_CREATE_INFO,
+                pNext: std::ptr::null(),
+                handleTypes: handle_types,
+            },
+        }
+    }
+
+    pub fn chain<'a>(
+        &'a mut self,
+        create_info: &vk::ImageCreateInfo,
+    ) -> Chained<'a, vk::ImageCreateInfo> {
+        let mut raw = *create_info.as_raw();
+        self.inner.pNext = raw.pNext;
+        raw.pNext = &self.inner as *const _ as *const c_void;
+
+        Chained {
+            info: vk::ImageCreateInfo::from_raw(raw),
+            _next: PhantomData,
+        }
+    }
+}
+
+/// Export info for [`PoolCreateInfo::with_export_memory`]. It must outlive the pool.
+pub struct ExportMemoryAllocateInfo {
+    inner: vk::sys::VkExportMemoryAllocateInfo,
+}
+
+impl ExportMemoryAllocateInfo {
+    pub const fn new(handle_types: vk::sys::VkExternalMemoryHandleTypeFlags) -> Self {
+        Self {
+            inner: vk::sys::VkExportMemoryAllocateInfo {
+                sType: vk::sys::VK_STRUCTURE_TYPE_EXPORT_MEMORY_ALLOCATE_INFO,
+                pNext: std::ptr::null(),
+                handleTypes: handle_types,
+            },
+        }
+    }
+
+    pub const fn as_ptr(&self) -> *const c_void {
+        &self.inner as *const _ as *const c_void
+    }
+}
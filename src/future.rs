@@ -0,0 +1,120 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+//! APIs that only exist in newer VMA headers. build.rs probes the header and enables each one
+//! it finds, so older SDKs still compile. [`FutureApi::is_compiled`] reports what made it in, and
+//! [`Allocator::is_api_available`] whether a given allocator can use it.
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FutureApi {
+    /// `Allocator::copy_memory_to_allocation` and `Allocator::copy_allocation_to_memory`.
+    CopyMemory,
+    /// `Allocator::export_win32_handle`, on Windows.
+    MemoryWin32Handle,
+}
+
+impl FutureApi {
+    pub const fn is_compiled(self) -> bool {
+        match self {
+            Self::CopyMemory => cfg!(vma_copy_memory),
+            Self::MemoryWin32Handle => cfg!(all(windows, vma_memory_win32_handle)),
+        }
+    }
+}
+
+impl Allocator {
+    /// Whether `api` is compiled in and this allocator was set up for it. Win32 handle export
+    /// needs an allocator created with `KHR_EXTERNAL_MEMORY_WIN32`.
+    pub fn is_api_available(&self, api: FutureApi) -> bool {
+        match api {
+            FutureApi::CopyMemory => api.is_compiled(),
+            #[cfg(all(windows, vma_memory_win32_handle))]
+            FutureApi::MemoryWin32Handle => create_state::get(*self).is_some_and(|state| {
+                state
+                    .flags
+                    .contains(AllocatorCreateFlags::KHR_EXTERNAL_MEMORY_WIN32)
+            }),
+            #[cfg(not(all(windows, vma_memory_win32_handle)))]
+            FutureApi::MemoryWin32Handle => false,
+        }
+    }
+}
+
+#[cfg(vma_copy_memory)]
+impl Allocator {
+    /// Maps, copies and flushes in one call. `dst_allocation` must be host-visible.
+    pub fn copy_memory_to_allocation(
+        &self,
+        src: &[u8],
+        dst_allocation: Allocation,
+        dst_offset: u64,
+    ) -> Result<(), Error> {
+        let result = unsafe {
+            vma_sys::vmaCopyMemoryToAllocation(
+                self.as_raw(),
+                src.as_ptr().cast(),
+                dst_allocation.as_raw(),
+                dst_offset,
+                src.len() as u64,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+        Ok(())
+    }
+
+    /// Invalidates, maps and copies in one call. `src_allocation` must be host-visible.
+    pub fn copy_allocation_to_memory(
+        &self,
+        src_allocation: Allocation,
+        src_offset: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Error> {
+        let result = unsafe {
+            vma_sys::vmaCopyAllocationToMemory(
+                self.as_raw(),
+                src_allocation.as_raw(),
+                src_offset,
+                dst.as_mut_ptr().cast(),
+                dst.len() as u64,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(windows, vma_memory_win32_handle))]
+impl Allocator {
+    /// Exports the allocation's memory through VMA, duplicated into `target_process` (null for
+    /// the current process). Requires an allocator created with external memory handle types
+    /// and `KHR_EXTERNAL_MEMORY_WIN32`, failing with [`Error::MissingFunction`] otherwise.
+    pub fn export_win32_handle(
+        &self,
+        allocation: Allocation,
+        target_process: vk::sys::HANDLE,
+    ) -> Result<vk::sys::HANDLE, Error> {
+        if !self.is_api_available(FutureApi::MemoryWin32Handle) {
+            return Err(Error::MissingFunction("vmaGetMemoryWin32Handle"));
+        }
+
+        let mut handle = std::ptr::null_mut();
+        let result = unsafe {
+            vma_sys::vmaGetMemoryWin32Handle(
+                self.as_raw(),
+                allocation.as_raw(),
+                target_process,
+                &mut handle,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+        Ok(handle)
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Bumped each time defragmentation moves an allocation to new memory. Cached device memory,
+/// offsets and descriptors built against an older version are stale.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AllocationVersion(u64);
+
+/// Bumped with any allocation's version, so caches can check for moves without locking.
+static EPOCH: AtomicU64 = AtomicU64::new(0);
+
+static GENERATIONS: Mutex<BTreeMap<(Allocator, Allocation), u64>> = Mutex::new(BTreeMap::new());
+
+/// Entries in `GENERATIONS`, so allocations that were never moved skip the lock.
+static LEN: AtomicUsize = AtomicUsize::new(0);
+
+fn generations() -> MutexGuard<'static, BTreeMap<(Allocator, Allocation), u64>> {
+    GENERATIONS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn is_empty() -> bool {
+    LEN.load(Ordering::Relaxed) == 0
+}
+
+pub(crate) fn bump(allocator: Allocator, allocation: Allocation) {
+    let mut generations = generations();
+    *generations.entry((allocator, allocation)).or_default() += 1;
+    LEN.store(generations.len(), Ordering::Relaxed);
+    EPOCH.fetch_add(1, Ordering::Release);
+}
+
+pub(crate) fn epoch() -> u64 {
+    EPOCH.load(Ordering::Acquire)
+}
+
+pub(crate) fn version(allocator: Allocator, allocation: Allocation) -> AllocationVersion {
+    if is_empty() {
+        return AllocationVersion::default();
+    }
+    AllocationVersion(
+        generations()
+            .get(&(allocator, allocation))
+            .copied()
+            .unwrap_or_default(),
+    )
+}
+
+pub(crate) fn host_bytes() -> usize {
+    generations().len() * std::mem::size_of::<((Allocator, Allocation), u64)>()
+}
+
+pub(crate) fn forget_allocation(allocator: Allocator, allocation: Allocation) {
+    if is_empty() {
+        return;
+    }
+    let mut generations = generations();
+    generations.remove(&(allocator, allocation));
+    LEN.store(generations.len(), Ordering::Relaxed);
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    if is_empty() {
+        return;
+    }
+    let mut generations = generations();
+    generations.retain(|(owner, _), _| *owner != allocator);
+    LEN.store(generations.len(), Ordering::Relaxed);
+}
@@ -0,0 +1,39 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// Approximate host bytes held by the crate's own bookkeeping. Entry sizes are counted, not the
+/// B-tree node overhead around them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HostMemoryUsage {
+    pub tracking: usize,
+    pub map_tracking: usize,
+    pub dedicated_thresholds: usize,
+    pub generations: usize,
+    pub block_sizes: usize,
+    pub create_states: usize,
+}
+
+impl HostMemoryUsage {
+    pub const fn total(&self) -> usize {
+        self.tracking
+            + self.map_tracking
+            + self.dedicated_thresholds
+            + self.generations
+            + self.block_sizes
+            + self.create_states
+    }
+}
+
+pub fn host_memory_usage() -> HostMemoryUsage {
+    HostMemoryUsage {
+        tracking: tracking::host_bytes(),
+        map_tracking: map_tracking::host_bytes(),
+        dedicated_thresholds: dedicated::host_bytes(),
+        generations: generation::host_bytes(),
+        block_sizes: block_size::host_bytes(),
+        create_states: create_state::host_bytes(),
+    }
+}
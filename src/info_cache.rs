@@ -0,0 +1,41 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// A caller-held copy of an allocation's info for hot paths. Reading it costs an atomic load
+/// rather than a call into VMA, and it refreshes itself once defragmentation has moved any
+/// allocation. Only the memory, offset, size and mapped pointer are kept current, and map and
+/// unmap calls are not seen, so it is meant for persistently mapped allocations.
+#[derive(Debug, Clone)]
+pub struct AllocationInfoCache {
+    allocator: Allocator,
+    allocation: Allocation,
+    epoch: u64,
+    info: AllocationInfo,
+}
+
+impl AllocationInfoCache {
+    pub fn new(allocator: &Allocator, allocation: Allocation) -> Self {
+        let epoch = generation::epoch();
+        Self {
+            allocator: *allocator,
+            allocation,
+            epoch,
+            info: allocator.get_allocation_info(allocation),
+        }
+    }
+
+    pub fn get(&mut self) -> &AllocationInfo {
+        let epoch = generation::epoch();
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.info = self.allocator.get_allocation_info(self.allocation);
+        }
+        &self.info
+    }
+
+    pub const fn allocation(&self) -> Allocation {
+        self.allocation
+    }
+}
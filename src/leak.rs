@@ -0,0 +1,46 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakedAllocation {
+    pub id: u64,
+    pub allocation: Allocation,
+    pub size: u64,
+    pub name: Option<String>,
+}
+
+/// The allocations still alive when an allocator was destroyed with
+/// [`Allocator::destroy_checked`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakReport {
+    pub allocation_count: u32,
+    pub allocation_bytes: u64,
+    /// The leaked allocations this crate created, by id, if object tracking is enabled.
+    /// Allocations made through the raw bindings are only counted.
+    pub allocations: Vec<LeakedAllocation>,
+}
+
+impl std::fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocations ({}) leaked",
+            self.allocation_count,
+            ByteSize(self.allocation_bytes)
+        )?;
+        for leaked in &self.allocations {
+            write!(
+                f,
+                "\n  #{} {} {}",
+                leaked.id,
+                leaked.name.as_deref().unwrap_or("<unnamed>"),
+                ByteSize(leaked.size)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LeakReport {}
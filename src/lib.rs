@@ -6,6 +6,12 @@ extern crate static_assertions;
 
 mod macros;
 
+mod error;
+pub use error::*;
+
+mod limits;
+pub use limits::*;
+
 mod enums;
 pub use enums::*;
 
@@ -15,9 +21,108 @@ pub use allocation::*;
 mod allocator;
 pub use allocator::*;
 
-use vma_sys::*;
+mod pool;
+pub use pool::*;
+
+mod defragmentation;
+pub use defragmentation::*;
+
+mod statistics;
+pub use statistics::*;
+
+mod vulkan_functions;
+pub use vulkan_functions::*;
+
+mod memory_properties;
+pub use memory_properties::*;
+
+mod planner;
+pub use planner::*;
+
+mod transient;
+pub use transient::*;
+
+mod virtual_block;
+pub use virtual_block::*;
+
+mod mapped_buffer;
+pub use mapped_buffer::*;
+
+mod suballocator;
+pub use suballocator::*;
+
+mod bucketing;
+pub use bucketing::*;
+
+mod device_allocator;
+pub use device_allocator::*;
+
+#[cfg(feature = "testing")]
+mod mock;
+#[cfg(feature = "testing")]
+pub use mock::*;
+
+#[cfg(feature = "residency")]
+mod residency;
+#[cfg(feature = "residency")]
+pub use residency::*;
+
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg(feature = "registry")]
+pub use registry::*;
+
+#[cfg(feature = "texture")]
+mod texture;
+#[cfg(feature = "texture")]
+pub use texture::*;
+
+#[cfg(feature = "profiling")]
+mod profiling;
+#[cfg(feature = "profiling")]
+pub use profiling::*;
+
+mod dedicated;
+
+mod create_state;
+
+mod block_size;
+pub use block_size::*;
+
+mod tracking;
+pub use tracking::*;
+
+mod generation;
+pub use generation::*;
+
+mod leak;
+pub use leak::*;
+
+mod info_cache;
+pub use info_cache::*;
+
+mod quota;
+pub use quota::*;
+
+mod scope;
+pub use scope::*;
+
+mod external_memory;
+pub use external_memory::*;
+
+#[cfg(windows)]
+mod win32;
+
+pub mod future;
+
+pub mod compat;
+
+mod host_usage;
+pub use host_usage::*;
+
+mod map_tracking;
+pub use map_tracking::*;
+
 use vulkan as vk;
 
 pub use vma_sys as sys;
-
-use std::mem::transmute;
@@ -0,0 +1,193 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// A create info that violates a device limit and would be rejected (or crash) in the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LimitError {
+    ZeroSizedBuffer,
+    BufferSize { size: u64, limit: u64 },
+    ImageExtent { extent: u32, limit: u32 },
+    ImageArrayLayers { layers: u32, limit: u32 },
+    ImageMipLevels { levels: u32, limit: u32 },
+    UnsupportedImageFormat { format: i32, usage: u32 },
+}
+
+impl std::fmt::Display for LimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ZeroSizedBuffer => write!(f, "buffer size must be greater than 0"),
+            Self::BufferSize { size, limit } => write!(
+                f,
+                "buffer size {} exceeds the device limit of {}",
+                size, limit
+            ),
+            Self::ImageExtent { extent, limit } => write!(
+                f,
+                "image extent {} exceeds the device limit of {}",
+                extent, limit
+            ),
+            Self::ImageArrayLayers { layers, limit } => write!(
+                f,
+                "image array layers {} exceed the device limit of {}",
+                layers, limit
+            ),
+            Self::ImageMipLevels { levels, limit } => write!(
+                f,
+                "image mip levels {} exceed the format limit of {}",
+                levels, limit
+            ),
+            Self::UnsupportedImageFormat { format, usage } => write!(
+                f,
+                "image format {} does not support usage {:#x} with this type, tiling and flags",
+                format, usage
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// `maxBufferSize` from `VkPhysicalDeviceMaintenance4Properties`, through the caller's
+/// `vkGetPhysicalDeviceProperties2`.
+pub(crate) fn max_buffer_size(
+    physical_device: vk::sys::VkPhysicalDevice,
+    get_physical_device_properties2: vk::sys::PFN_vkGetPhysicalDeviceProperties2,
+) -> Option<u64> {
+    let get_physical_device_properties2 = get_physical_device_properties2?;
+
+    let mut maintenance4: vk::sys::VkPhysicalDeviceMaintenance4Properties =
+        unsafe { std::mem::zeroed() };
+    maintenance4.sType = vk::sys::VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_MAINTENANCE_4_PROPERTIES;
+
+    let mut properties: vk::sys::VkPhysicalDeviceProperties2 = unsafe { std::mem::zeroed() };
+    properties.sType = vk::sys::VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_PROPERTIES_2;
+    properties.pNext = std::ptr::addr_of_mut!(maintenance4).cast();
+
+    unsafe { get_physical_device_properties2(physical_device, &mut properties) };
+
+    // Left at 0 by drivers without maintenance4.
+    (maintenance4.maxBufferSize != 0).then_some(maintenance4.maxBufferSize)
+}
+
+/// What the device supports for the format, type, tiling, usage and flags of `create_info`.
+pub(crate) fn image_format_properties(
+    physical_device: vk::sys::VkPhysicalDevice,
+    get_physical_device_image_format_properties: vk::sys::PFN_vkGetPhysicalDeviceImageFormatProperties,
+    create_info: &vk::sys::VkImageCreateInfo,
+) -> Result<Option<vk::sys::VkImageFormatProperties>, LimitError> {
+    let Some(get_physical_device_image_format_properties) =
+        get_physical_device_image_format_properties
+    else {
+        return Ok(None);
+    };
+
+    let mut properties = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        get_physical_device_image_format_properties(
+            physical_device,
+            create_info.format,
+            create_info.imageType,
+            create_info.tiling,
+            create_info.usage,
+            create_info.flags,
+            &mut properties,
+        )
+    };
+
+    match result {
+        vk::sys::VK_SUCCESS => Ok(Some(properties)),
+        _ => Err(LimitError::UnsupportedImageFormat {
+            format: create_info.format,
+            usage: create_info.usage,
+        }),
+    }
+}
+
+/// `maxStorageBufferRange` and `maxUniformBufferRange` limit descriptor ranges, not buffer sizes,
+/// so they are not checked here.
+pub(crate) fn check_buffer(
+    max_buffer_size: Option<u64>,
+    create_info: &vk::sys::VkBufferCreateInfo,
+) -> Result<(), LimitError> {
+    let size = create_info.size;
+    if size == 0 {
+        return Err(LimitError::ZeroSizedBuffer);
+    }
+
+    if let Some(limit) = max_buffer_size {
+        if size > limit {
+            return Err(LimitError::BufferSize { size, limit });
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_image(
+    limits: &vk::sys::VkPhysicalDeviceLimits,
+    format_properties: Option<&vk::sys::VkImageFormatProperties>,
+    create_info: &vk::sys::VkImageCreateInfo,
+) -> Result<(), LimitError> {
+    let extent = create_info.extent;
+    let is_cube = create_info.flags & vk::sys::VK_IMAGE_CREATE_CUBE_COMPATIBLE_BIT as u32 != 0;
+
+    let (largest, limit) = match create_info.imageType {
+        vk::sys::VK_IMAGE_TYPE_1D => (extent.width, limits.maxImageDimension1D),
+        vk::sys::VK_IMAGE_TYPE_2D if is_cube => (
+            extent.width.max(extent.height),
+            limits.maxImageDimensionCube,
+        ),
+        vk::sys::VK_IMAGE_TYPE_2D => (extent.width.max(extent.height), limits.maxImageDimension2D),
+        _ => (
+            extent.width.max(extent.height).max(extent.depth),
+            limits.maxImageDimension3D,
+        ),
+    };
+
+    if largest > limit {
+        return Err(LimitError::ImageExtent {
+            extent: largest,
+            limit,
+        });
+    }
+
+    if create_info.arrayLayers > limits.maxImageArrayLayers {
+        return Err(LimitError::ImageArrayLayers {
+            layers: create_info.arrayLayers,
+            limit: limits.maxImageArrayLayers,
+        });
+    }
+
+    let Some(format_properties) = format_properties else {
+        return Ok(());
+    };
+
+    let max_extent = format_properties.maxExtent;
+    for (extent, limit) in [
+        (extent.width, max_extent.width),
+        (extent.height, max_extent.height),
+        (extent.depth, max_extent.depth),
+    ] {
+        if extent > limit {
+            return Err(LimitError::ImageExtent { extent, limit });
+        }
+    }
+
+    if create_info.mipLevels > format_properties.maxMipLevels {
+        return Err(LimitError::ImageMipLevels {
+            levels: create_info.mipLevels,
+            limit: format_properties.maxMipLevels,
+        });
+    }
+
+    if create_info.arrayLayers > format_properties.maxArrayLayers {
+        return Err(LimitError::ImageArrayLayers {
+            layers: create_info.arrayLayers,
+            limit: format_properties.maxArrayLayers,
+        });
+    }
+
+    Ok(())
+}
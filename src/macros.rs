@@ -9,13 +9,19 @@ macro_rules! vma_handle {
         }
 
         impl $name {
-            pub const fn from_raw(handle: $ty) -> Self {
+            /// # Safety
+            /// `handle` must be a live handle created by VMA, or null where the API accepts it.
+            pub const unsafe fn from_raw(handle: $ty) -> Self {
                 Self { handle }
             }
 
             pub const fn as_raw(&self) -> $ty {
                 self.handle
             }
+
+            pub fn is_null(&self) -> bool {
+                self.handle.is_null()
+            }
         }
 
         impl std::fmt::Debug for $name {
@@ -41,6 +47,37 @@ macro_rules! vma_handle {
 
 macro_rules! vma_struct {
     ($name:tt, $ty:tt) => {
+        vma_struct!(@struct $name, $ty);
+
+        impl $name {
+            pub const fn from_raw(inner: $ty) -> Self {
+                Self { inner }
+            }
+        }
+    };
+    // For structs whose accessors dereference the pointers they hold.
+    ($name:tt, $ty:tt, unsafe) => {
+        vma_struct!(@struct $name, $ty);
+
+        impl $name {
+            /// # Safety
+            /// Every pointer in `inner` must be null or valid for as long as the struct is used,
+            /// as when filled in by VMA.
+            pub const unsafe fn from_raw(inner: $ty) -> Self {
+                Self { inner }
+            }
+        }
+    };
+    ($name:tt, $ty:tt, Clone) => {
+        vma_struct!($name, $ty);
+        vma_struct!(@clone $name);
+    };
+    ($name:tt, $ty:tt, Clone, unsafe) => {
+        vma_struct!($name, $ty, unsafe);
+        vma_struct!(@clone $name);
+    };
+    (@struct $name:tt, $ty:tt) => {
+        #[repr(transparent)]
         pub struct $name {
             inner: $ty,
         }
@@ -52,15 +89,17 @@ macro_rules! vma_struct {
                 }
             }
 
-            pub const fn from_raw(inner: $ty) -> Self {
-                Self { inner }
-            }
-
             pub const fn as_raw(&self) -> &$ty {
                 &self.inner
             }
         }
 
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
         impl std::fmt::Debug for $name {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 self.inner.fmt(f)
@@ -69,7 +108,23 @@ macro_rules! vma_struct {
 
         assert_eq_size!($name, $ty);
     };
+    (@clone $name:tt) => {
+        // Shallow: anything the struct points to is shared, not copied.
+        impl Clone for $name {
+            fn clone(&self) -> Self {
+                Self { inner: self.inner }
+            }
+        }
+    };
+}
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+    };
 }
 
+pub(crate) use trace;
 pub(crate) use vma_handle;
 pub(crate) use vma_struct;
@@ -0,0 +1,295 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappedAllocation {
+    pub allocation: Allocation,
+    pub id: Option<u64>,
+    pub map_count: u32,
+    pub mapped_for: Duration,
+}
+
+/// A misuse found by map tracking, see [`Allocator::set_map_warning_callback`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapWarning {
+    UnbalancedUnmap {
+        allocation: Allocation,
+    },
+    FreedWhileMapped {
+        allocation: Allocation,
+        map_count: u32,
+    },
+    LongLived {
+        allocation: Allocation,
+        mapped_for: Duration,
+    },
+}
+
+impl std::fmt::Display for MapWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnbalancedUnmap { allocation } => {
+                write!(f, "{} unmapped without a matching map", allocation)
+            }
+            Self::FreedWhileMapped {
+                allocation,
+                map_count,
+            } => write!(
+                f,
+                "{} freed while still mapped {} time(s)",
+                allocation, map_count
+            ),
+            Self::LongLived {
+                allocation,
+                mapped_for,
+            } => write!(f, "{} has been mapped for {:?}", allocation, mapped_for),
+        }
+    }
+}
+
+pub type MapWarningCallback = fn(Allocator, &MapWarning);
+
+#[derive(Debug, Clone, Copy)]
+struct MapRecord {
+    map_count: u32,
+    mapped_at: Instant,
+    reported: bool,
+}
+
+#[derive(Default)]
+struct MapTracker {
+    maps: BTreeMap<Allocation, MapRecord>,
+    warn_after: Option<Duration>,
+    callback: Option<MapWarningCallback>,
+}
+
+static TRACKERS: Mutex<BTreeMap<Allocator, MapTracker>> = Mutex::new(BTreeMap::new());
+
+/// Allocators with tracking enabled, so untracked maps skip the lock entirely.
+static TRACKED_ALLOCATORS: AtomicUsize = AtomicUsize::new(0);
+
+fn trackers() -> MutexGuard<'static, BTreeMap<Allocator, MapTracker>> {
+    TRACKERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn any_tracked() -> bool {
+    TRACKED_ALLOCATORS.load(Ordering::Relaxed) != 0
+}
+
+/// Reports warnings after the lock is released, so callbacks may use the allocator.
+fn warn(allocator: Allocator, callback: Option<MapWarningCallback>, warnings: &[MapWarning]) {
+    for _warning in warnings {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(%allocator, "{}", _warning);
+        if let Some(callback) = callback {
+            callback(allocator, _warning);
+        }
+    }
+}
+
+pub(crate) fn enable(allocator: Allocator) {
+    if let Entry::Vacant(entry) = trackers().entry(allocator) {
+        entry.insert(MapTracker::default());
+        TRACKED_ALLOCATORS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    if !any_tracked() {
+        return;
+    }
+    if trackers().remove(&allocator).is_some() {
+        TRACKED_ALLOCATORS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn set_warn_after(allocator: Allocator, threshold: Option<Duration>) {
+    enable(allocator);
+    if let Some(tracker) = trackers().get_mut(&allocator) {
+        tracker.warn_after = threshold;
+    }
+}
+
+pub(crate) fn set_callback(allocator: Allocator, callback: Option<MapWarningCallback>) {
+    enable(allocator);
+    if let Some(tracker) = trackers().get_mut(&allocator) {
+        tracker.callback = callback;
+    }
+}
+
+pub(crate) fn host_bytes() -> usize {
+    let entry = std::mem::size_of::<(Allocation, MapRecord)>();
+    let trackers = trackers();
+    trackers.len() * std::mem::size_of::<(Allocator, MapTracker)>()
+        + trackers
+            .values()
+            .map(|tracker| tracker.maps.len() * entry)
+            .sum::<usize>()
+}
+
+pub(crate) fn track_map(allocator: Allocator, allocation: Allocation) {
+    if !any_tracked() {
+        return;
+    }
+    if let Some(tracker) = trackers().get_mut(&allocator) {
+        tracker
+            .maps
+            .entry(allocation)
+            .and_modify(|record| record.map_count += 1)
+            .or_insert(MapRecord {
+                map_count: 1,
+                mapped_at: Instant::now(),
+                reported: false,
+            });
+    }
+}
+
+pub(crate) fn track_unmap(allocator: Allocator, allocation: Allocation) {
+    if !any_tracked() {
+        return;
+    }
+
+    let (callback, warning) = {
+        let mut trackers = trackers();
+        let Some(tracker) = trackers.get_mut(&allocator) else {
+            return;
+        };
+
+        let warning = match tracker.maps.get_mut(&allocation) {
+            None => Some(MapWarning::UnbalancedUnmap { allocation }),
+            Some(record) if record.map_count > 1 => {
+                record.map_count -= 1;
+                None
+            }
+            Some(record) => {
+                let record = *record;
+                tracker.maps.remove(&allocation);
+
+                let mapped_for = record.mapped_at.elapsed();
+                // Maps already reported by check_long_lived_maps are not reported again.
+                let long_lived = tracker
+                    .warn_after
+                    .is_some_and(|threshold| mapped_for >= threshold);
+                (long_lived && !record.reported).then_some(MapWarning::LongLived {
+                    allocation,
+                    mapped_for,
+                })
+            }
+        };
+        (tracker.callback, warning)
+    };
+
+    warn(allocator, callback, warning.as_slice());
+}
+
+pub(crate) fn forget_allocation(allocator: Allocator, allocation: Allocation) {
+    if !any_tracked() {
+        return;
+    }
+
+    let (callback, warning) = {
+        let mut trackers = trackers();
+        let Some(tracker) = trackers.get_mut(&allocator) else {
+            return;
+        };
+        let warning = tracker
+            .maps
+            .remove(&allocation)
+            .map(|record| MapWarning::FreedWhileMapped {
+                allocation,
+                map_count: record.map_count,
+            });
+        (tracker.callback, warning)
+    };
+
+    warn(allocator, callback, warning.as_slice());
+}
+
+pub(crate) fn mapped_allocations(
+    allocator: Allocator,
+    older_than: Duration,
+) -> Vec<MappedAllocation> {
+    if !any_tracked() {
+        return Vec::new();
+    }
+
+    let now = Instant::now();
+    let mut mapped: Vec<MappedAllocation> = trackers()
+        .get(&allocator)
+        .map(|tracker| {
+            tracker
+                .maps
+                .iter()
+                .map(|(allocation, record)| MappedAllocation {
+                    allocation: *allocation,
+                    id: None,
+                    map_count: record.map_count,
+                    mapped_for: now.duration_since(record.mapped_at),
+                })
+                .filter(|mapped| mapped.mapped_for >= older_than)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for mapped in &mut mapped {
+        mapped.id = tracking::id_of(allocator, mapped.allocation.into());
+    }
+    mapped
+}
+
+/// Warns about maps that crossed the threshold since the last check, each only once.
+pub(crate) fn check_long_lived(allocator: Allocator) -> Vec<MappedAllocation> {
+    if !any_tracked() {
+        return Vec::new();
+    }
+
+    let now = Instant::now();
+    let (callback, mut mapped) = {
+        let mut trackers = trackers();
+        let Some(tracker) = trackers.get_mut(&allocator) else {
+            return Vec::new();
+        };
+        let Some(threshold) = tracker.warn_after else {
+            return Vec::new();
+        };
+
+        let mapped: Vec<MappedAllocation> = tracker
+            .maps
+            .iter_mut()
+            .filter(|(_, record)| {
+                !record.reported && now.duration_since(record.mapped_at) >= threshold
+            })
+            .map(|(allocation, record)| {
+                record.reported = true;
+                MappedAllocation {
+                    allocation: *allocation,
+                    id: None,
+                    map_count: record.map_count,
+                    mapped_for: now.duration_since(record.mapped_at),
+                }
+            })
+            .collect();
+        (tracker.callback, mapped)
+    };
+
+    let warnings: Vec<MapWarning> = mapped
+        .iter()
+        .map(|mapped| MapWarning::LongLived {
+            allocation: mapped.allocation,
+            mapped_for: mapped.mapped_for,
+        })
+        .collect();
+    warn(allocator, callback, &warnings);
+
+    for mapped in &mut mapped {
+        mapped.id = tracking::id_of(allocator, mapped.allocation.into());
+    }
+    mapped
+}
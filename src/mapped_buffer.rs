@@ -0,0 +1,146 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::ptr::NonNull;
+
+/// Plain data that can be read straight out of mapped memory, which may hold any bytes.
+///
+/// # Safety
+/// Every bit pattern of `size_of::<Self>()` bytes must be a valid `Self`, and `Self` must have no
+/// padding, as writes copy it to the device byte for byte.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($ty:ty),*) => {
+        $(unsafe impl Pod for $ty {})*
+    };
+}
+
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// A buffer created with `MAPPED` that stays mapped for its whole life, viewed as `[T]`.
+///
+/// Writes through [`MappedBuffer::write`] are flushed; after writing through
+/// [`MappedBuffer::as_mut_slice`], call [`MappedBuffer::flush`]. VMA skips the flush for
+/// `HOST_COHERENT` memory, so it is always safe to call.
+pub struct MappedBuffer<T: Pod = u8> {
+    allocator: Allocator,
+    buffer: vk::Buffer,
+    allocation: Allocation,
+    data: NonNull<T>,
+    len: usize,
+}
+
+unsafe impl<T: Pod + Send> Send for MappedBuffer<T> {}
+unsafe impl<T: Pod + Sync> Sync for MappedBuffer<T> {}
+
+impl<T: Pod> MappedBuffer<T> {
+    /// Adds `MAPPED`, and `HOST_ACCESS_SEQUENTIAL_WRITE` if no host access flag is set. Fails with
+    /// [`Error::MemoryMapFailed`], destroying the buffer, if the memory is not mapped or not
+    /// aligned for `T`.
+    pub fn create(
+        allocator: &Allocator,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Self, Error> {
+        let mut raw = *allocation_create_info.as_raw();
+        let mut flags = AllocationCreateFlags::from_bits_truncate(raw.flags);
+        if !flags.intersects(
+            AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE
+                | AllocationCreateFlags::HOST_ACCESS_RANDOM,
+        ) {
+            flags |= AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE;
+        }
+        raw.flags = (flags | AllocationCreateFlags::MAPPED).bits();
+
+        let AllocatedBuffer {
+            buffer,
+            allocation,
+            info,
+        } = allocator
+            .create_allocated_buffer(buffer_create_info, &AllocationCreateInfo::from_raw(raw))?;
+
+        let data =
+            NonNull::new(info.mapped_data().cast::<T>()).filter(|data| data.as_ptr().is_aligned());
+        let Some(data) = data else {
+            allocator.destroy_buffer(buffer, allocation);
+            return Err(Error::MemoryMapFailed);
+        };
+
+        Ok(Self {
+            allocator: *allocator,
+            buffer,
+            allocation,
+            data,
+            len: buffer_create_info.as_raw().size as usize / std::mem::size_of::<T>().max(1),
+        })
+    }
+
+    pub fn destroy(self) {
+        self.allocator.destroy_buffer(self.buffer, self.allocation);
+    }
+
+    pub const fn buffer(&self) -> vk::Buffer {
+        self.buffer
+    }
+
+    pub const fn allocation(&self) -> Allocation {
+        self.allocation
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.data.as_ptr(), self.len) }
+    }
+
+    /// Copies `values` in starting at element `index`, then flushes the written range. Fails with
+    /// [`Error::OutOfBounds`], writing nothing, if the range does not fit in the buffer.
+    pub fn write(&mut self, index: usize, values: &[T]) -> Result<(), Error> {
+        let out_of_bounds = Error::OutOfBounds {
+            offset: index as u64,
+            len: values.len() as u64,
+            size: self.len as u64,
+        };
+        let range = index..index.checked_add(values.len()).ok_or(out_of_bounds)?;
+
+        self.as_mut_slice()
+            .get_mut(range)
+            .ok_or(out_of_bounds)?
+            .copy_from_slice(values);
+        self.flush(index, values.len())
+    }
+
+    /// Flushes `len` elements starting at `index`. Fails with [`Error::OutOfBounds`] if the range
+    /// does not fit in the buffer.
+    pub fn flush(&self, index: usize, len: usize) -> Result<(), Error> {
+        let out_of_bounds = Error::OutOfBounds {
+            offset: index as u64,
+            len: len as u64,
+            size: self.len as u64,
+        };
+        let end = index.checked_add(len).ok_or(out_of_bounds)?;
+        if end > self.len {
+            return Err(out_of_bounds);
+        }
+
+        let element = std::mem::size_of::<T>() as u64;
+        let offset = (index as u64).checked_mul(element).ok_or(out_of_bounds)?;
+        let size = (len as u64).checked_mul(element).ok_or(out_of_bounds)?;
+        self.allocator
+            .flush_allocation(self.allocation, offset, size)
+    }
+}
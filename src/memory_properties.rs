@@ -0,0 +1,42 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryHeap {
+    pub index: u32,
+    pub size: u64,
+    pub flags: vk::MemoryHeapFlags,
+}
+
+impl std::fmt::Display for MemoryHeap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "heap {}: {} flags {:#x}",
+            self.index,
+            ByteSize(self.size),
+            self.flags.bits()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryType {
+    pub index: u32,
+    pub heap_index: u32,
+    pub property_flags: vk::MemoryPropertyFlags,
+}
+
+impl std::fmt::Display for MemoryType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "type {}: heap {} flags {:#x}",
+            self.index,
+            self.heap_index,
+            self.property_flags.bits()
+        )
+    }
+}
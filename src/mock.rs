@@ -0,0 +1,156 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+use std::sync::{Mutex, MutexGuard};
+
+struct MockAllocation {
+    data: Vec<u8>,
+    map_count: u32,
+}
+
+#[derive(Default)]
+struct MockState {
+    next_handle: usize,
+    allocations: BTreeMap<Allocation, MockAllocation>,
+    flushes: usize,
+}
+
+/// A [`DeviceAllocator`] backed by host memory, for testing resource managers without a GPU.
+///
+/// Handles are fake and must never reach Vulkan. Buffers get `size` bytes of mappable memory;
+/// images get none, since their size depends on the driver.
+#[derive(Default)]
+pub struct MockAllocator {
+    state: Mutex<MockState>,
+    /// When set, allocations larger than this fail with [`Error::OutOfDeviceMemory`].
+    pub fail_above: Option<u64>,
+}
+
+impl MockAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn allocate(&self, size: u64) -> Result<(usize, Allocation), Error> {
+        if self.fail_above.is_some_and(|limit| size > limit) {
+            return Err(Error::OutOfDeviceMemory);
+        }
+
+        let mut state = self.state();
+        state.next_handle += 1;
+        let handle = state.next_handle;
+        // Never reaches VMA, so a fake handle is fine.
+        let allocation = unsafe { Allocation::from_raw(handle as _) };
+        state.allocations.insert(
+            allocation,
+            MockAllocation {
+                data: vec![0; size as usize],
+                map_count: 0,
+            },
+        );
+        Ok((handle, allocation))
+    }
+
+    fn release(&self, allocation: Allocation) {
+        let removed = self.state().allocations.remove(&allocation);
+        assert!(
+            removed.is_some(),
+            "{} freed twice or never allocated",
+            allocation
+        );
+    }
+
+    pub fn live_allocations(&self) -> usize {
+        self.state().allocations.len()
+    }
+
+    pub fn is_mapped(&self, allocation: Allocation) -> bool {
+        self.state()
+            .allocations
+            .get(&allocation)
+            .is_some_and(|allocation| allocation.map_count > 0)
+    }
+
+    pub fn flush_count(&self) -> usize {
+        self.state().flushes
+    }
+}
+
+impl DeviceAllocator for MockAllocator {
+    fn create_buffer(
+        &self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        _allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Buffer, Allocation), Error> {
+        let (handle, allocation) = self.allocate(buffer_create_info.as_raw().size)?;
+        Ok((vk::Buffer::from_raw(handle as _), allocation))
+    }
+
+    fn destroy_buffer(&self, _buffer: vk::Buffer, allocation: Allocation) {
+        self.release(allocation);
+    }
+
+    fn create_image(
+        &self,
+        _image_create_info: &vk::ImageCreateInfo,
+        _allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Image, Allocation), Error> {
+        let (handle, allocation) = self.allocate(0)?;
+        Ok((vk::Image::from_raw(handle as _), allocation))
+    }
+
+    fn destroy_image(&self, _image: vk::Image, allocation: Allocation) {
+        self.release(allocation);
+    }
+
+    fn allocate_memory(
+        &self,
+        memory_requirements: &vk::MemoryRequirements,
+        _allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Allocation, Error> {
+        let (_, allocation) = self.allocate(memory_requirements.as_raw().size)?;
+        Ok(allocation)
+    }
+
+    fn free_memory(&self, allocation: Allocation) {
+        self.release(allocation);
+    }
+
+    fn map_memory(&self, allocation: Allocation) -> Result<NonNull<c_void>, Error> {
+        let mut state = self.state();
+        let allocation = state
+            .allocations
+            .get_mut(&allocation)
+            .ok_or(Error::MemoryMapFailed)?;
+        allocation.map_count += 1;
+        Ok(NonNull::new(allocation.data.as_mut_ptr().cast()).unwrap_or(NonNull::dangling()))
+    }
+
+    fn unmap_memory(&self, allocation: Allocation) {
+        let mut state = self.state();
+        let allocation = state
+            .allocations
+            .get_mut(&allocation)
+            .expect("unmapping an unknown allocation");
+        assert!(allocation.map_count > 0, "unbalanced unmap");
+        allocation.map_count -= 1;
+    }
+
+    fn flush_allocation(
+        &self,
+        _allocation: Allocation,
+        _offset: u64,
+        _size: u64,
+    ) -> Result<(), Error> {
+        self.state().flushes += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+pub enum ResourceDesc<'a> {
+    Buffer {
+        create_info: &'a vk::BufferCreateInfo,
+        allocation_create_info: &'a AllocationCreateInfo,
+        count: u32,
+    },
+    /// Image sizes depend on the driver's layout, so images are only planned when the planner has
+    /// the memory requirements query, see [`MemoryPlanner::with_memory_requirements`].
+    Image {
+        create_info: &'a vk::ImageCreateInfo,
+        allocation_create_info: &'a AllocationCreateInfo,
+        count: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapPlan {
+    pub heap_index: u32,
+    pub usage: u64,
+    pub budget: u64,
+    pub planned_bytes: u64,
+}
+
+impl HeapPlan {
+    pub const fn projected_usage(&self) -> u64 {
+        self.usage.saturating_add(self.planned_bytes)
+    }
+
+    pub const fn fits(&self) -> bool {
+        self.projected_usage() <= self.budget
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlanReport {
+    pub heaps: Vec<HeapPlan>,
+    /// Number of resources for which no memory type or size could be found.
+    pub unplaceable: usize,
+}
+
+impl PlanReport {
+    pub fn fits(&self) -> bool {
+        self.unplaceable == 0 && self.heaps.iter().all(HeapPlan::fits)
+    }
+
+    pub fn planned_bytes(&self) -> u64 {
+        self.heaps.iter().map(|heap| heap.planned_bytes).sum()
+    }
+}
+
+/// Estimates whether a set of resources would fit in the current heap budgets.
+pub struct MemoryPlanner<'a> {
+    allocator: &'a Allocator,
+    get_device_buffer_memory_requirements: vk::sys::PFN_vkGetDeviceBufferMemoryRequirements,
+    get_device_image_memory_requirements: vk::sys::PFN_vkGetDeviceImageMemoryRequirements,
+}
+
+impl<'a> MemoryPlanner<'a> {
+    pub const fn new(allocator: &'a Allocator) -> Self {
+        Self {
+            allocator,
+            get_device_buffer_memory_requirements: None,
+            get_device_image_memory_requirements: None,
+        }
+    }
+
+    /// Plans each resource by its memory requirements, rounded up to their alignment, instead of
+    /// the raw buffer size. Needs the maintenance4 entry points, see
+    /// [`Allocator::buffer_memory_requirements`].
+    pub fn with_memory_requirements(
+        mut self,
+        get_device_buffer_memory_requirements: vk::sys::PFN_vkGetDeviceBufferMemoryRequirements,
+        get_device_image_memory_requirements: vk::sys::PFN_vkGetDeviceImageMemoryRequirements,
+    ) -> Self {
+        self.get_device_buffer_memory_requirements = get_device_buffer_memory_requirements;
+        self.get_device_image_memory_requirements = get_device_image_memory_requirements;
+        self
+    }
+
+    /// The memory type and aligned size of one resource. The memory type is picked from the
+    /// create info, so AUTO usages work; the requirements only give the size. Buffers fall back to
+    /// their raw size without the requirements query, images cannot.
+    fn place(&self, desc: &ResourceDesc) -> Result<(u32, u64), Error> {
+        let (memory_type, requirements) = match desc {
+            ResourceDesc::Buffer {
+                create_info,
+                allocation_create_info,
+                ..
+            } => {
+                let memory_type = self
+                    .allocator
+                    .find_memory_type_index_for_buffer_info(create_info, allocation_create_info)?;
+                match self.get_device_buffer_memory_requirements {
+                    Some(_) => (
+                        memory_type,
+                        self.allocator.buffer_memory_requirements(
+                            create_info,
+                            self.get_device_buffer_memory_requirements,
+                        )?,
+                    ),
+                    None => return Ok((memory_type, create_info.as_raw().size)),
+                }
+            }
+            ResourceDesc::Image {
+                create_info,
+                allocation_create_info,
+                ..
+            } => (
+                self.allocator
+                    .find_memory_type_index_for_image_info(create_info, allocation_create_info)?,
+                self.allocator.image_memory_requirements(
+                    create_info,
+                    self.get_device_image_memory_requirements,
+                )?,
+            ),
+        };
+
+        let requirements = requirements.as_raw();
+        let size = requirements
+            .size
+            .checked_next_multiple_of(requirements.alignment.max(1))
+            .unwrap_or(u64::MAX);
+        Ok((memory_type, size))
+    }
+
+    pub fn estimate_settings(&self, preset: &[ResourceDesc]) -> PlanReport {
+        let properties = self.allocator.raw_memory_properties();
+
+        let mut report = PlanReport {
+            heaps: self
+                .allocator
+                .get_heap_budgets()
+                .iter()
+                .enumerate()
+                .map(|(heap_index, budget)| HeapPlan {
+                    heap_index: heap_index as u32,
+                    usage: budget.usage,
+                    budget: budget.budget,
+                    planned_bytes: 0,
+                })
+                .collect(),
+            unplaceable: 0,
+        };
+
+        for desc in preset {
+            let count = match desc {
+                ResourceDesc::Buffer { count, .. } | ResourceDesc::Image { count, .. } => *count,
+            };
+
+            let (memory_type, size) = match self.place(desc) {
+                Ok(placed) => placed,
+                Err(_) => {
+                    report.unplaceable += count as usize;
+                    continue;
+                }
+            };
+
+            let heap_index = properties.memoryTypes[memory_type as usize].heapIndex as usize;
+            let heap = &mut report.heaps[heap_index];
+            heap.planned_bytes = heap
+                .planned_bytes
+                .saturating_add(size.saturating_mul(count as u64));
+        }
+
+        report
+    }
+}
@@ -0,0 +1,389 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::macros::*;
+use crate::*;
+use vma_sys::*;
+
+vma_handle!(Pool, VmaPool);
+
+impl Pool {
+    pub fn builder(allocator: &Allocator) -> PoolBuilder<'_> {
+        PoolBuilder {
+            allocator,
+            resource: None,
+            allocation_create_info: AllocationCreateInfo::auto(),
+            create_info: PoolCreateInfo::new(),
+        }
+    }
+
+    /// Runs defragmentation scoped to this pool, handing each pass's moves to `handle_moves`.
+    ///
+    /// Meant for retrying after an allocation fails with [`Error::FragmentedPool`].
+    pub fn compact<F>(
+        &self,
+        allocator: &Allocator,
+        mut handle_moves: F,
+    ) -> Result<DefragmentationStats, Error>
+    where
+        F: FnMut(&mut [DefragmentationMove]),
+    {
+        let info = DefragmentationInfo::for_pool(*self);
+        let context = allocator.begin_defragmentation(&info)?;
+
+        let result = loop {
+            let mut pass = match allocator.begin_defragmentation_pass(context) {
+                Ok(Some(pass)) => pass,
+                Ok(None) => break Ok(()),
+                Err(err) => break Err(err),
+            };
+
+            handle_moves(pass.moves_mut());
+
+            match allocator.end_defragmentation_pass(context, &mut pass) {
+                Ok(true) => break Ok(()),
+                Ok(false) => continue,
+                Err(err) => break Err(err),
+            }
+        };
+
+        let stats = allocator.end_defragmentation(context);
+        result.map(|()| stats)
+    }
+}
+
+/// The smallest explicit block size [`PoolCreateInfo::validate`] accepts, one 4 KiB page. Smaller
+/// blocks cost a device memory allocation each for almost no space.
+pub const MIN_POOL_BLOCK_SIZE: u64 = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolCreateInfoError {
+    /// The block size is below [`MIN_POOL_BLOCK_SIZE`].
+    BlockSizeTooSmall(u64),
+    /// The block size is neither a power of two nor a whole number of MiB.
+    UnusualBlockSize(u64),
+    MinBlockCountAboveMax {
+        min: usize,
+        max: usize,
+    },
+    MinAlignmentNotPowerOfTwo(u64),
+}
+
+impl std::fmt::Display for PoolCreateInfoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BlockSizeTooSmall(size) => write!(
+                f,
+                "block size {} is below the minimum of {}",
+                size, MIN_POOL_BLOCK_SIZE
+            ),
+            Self::UnusualBlockSize(size) => write!(
+                f,
+                "block size {} is neither a power of two nor a multiple of 1 MiB",
+                size
+            ),
+            Self::MinBlockCountAboveMax { min, max } => write!(
+                f,
+                "min block count {} is greater than max block count {}",
+                min, max
+            ),
+            Self::MinAlignmentNotPowerOfTwo(alignment) => write!(
+                f,
+                "min allocation alignment {} is not a power of two",
+                alignment
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PoolCreateInfoError {}
+
+vma_struct!(PoolCreateInfo, VmaPoolCreateInfo, Clone);
+
+impl PoolCreateInfo {
+    pub fn with_memory_type_index(mut self, index: u32) -> Self {
+        self.inner.memoryTypeIndex = index;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: PoolCreateFlags) -> Self {
+        self.inner.flags = flags.bits();
+        self
+    }
+
+    /// 0 lets VMA pick the block size.
+    pub fn with_block_size(mut self, size: u64) -> Self {
+        self.inner.blockSize = size;
+        self
+    }
+
+    /// Blocks allocated up front and never freed.
+    pub fn with_min_block_count(mut self, count: usize) -> Self {
+        self.inner.minBlockCount = count;
+        self
+    }
+
+    /// 0 means no limit.
+    pub fn with_max_block_count(mut self, count: usize) -> Self {
+        self.inner.maxBlockCount = count;
+        self
+    }
+
+    pub fn with_min_allocation_alignment(mut self, alignment: u64) -> Self {
+        self.inner.minAllocationAlignment = alignment;
+        self
+    }
+
+    /// Chained to every `VkMemoryAllocateInfo` of the pool, e.g. for export info.
+    ///
+    /// # Safety
+    /// `next` must be null or point to a valid pNext chain that outlives the pool.
+    pub unsafe fn with_memory_allocate_next(mut self, next: *const std::ffi::c_void) -> Self {
+        self.inner.pMemoryAllocateNext = next as *mut _;
+        self
+    }
+
+    /// Makes the pool's blocks exportable, see [`ExportMemoryAllocateInfo`].
+    ///
+    /// # Safety
+    /// `export` is kept by pointer and must outlive the pool.
+    pub unsafe fn with_export_memory(self, export: &ExportMemoryAllocateInfo) -> Self {
+        self.with_memory_allocate_next(export.as_ptr())
+    }
+
+    /// Skips `bufferImageGranularity` padding. Only safe if the pool never holds both linear
+    /// resources (buffers, linear images) and optimal-tiling images; see [`TilingPools`].
+    pub fn ignore_buffer_image_granularity(mut self) -> Self {
+        self.inner.flags |= PoolCreateFlags::IGNORE_BUFFER_IMAGE_GRANULARITY.bits();
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), PoolCreateInfoError> {
+        let block_size = self.inner.blockSize;
+        if block_size != 0 && block_size < MIN_POOL_BLOCK_SIZE {
+            return Err(PoolCreateInfoError::BlockSizeTooSmall(block_size));
+        }
+        if block_size != 0 && !block_size.is_power_of_two() && !block_size.is_multiple_of(1 << 20) {
+            return Err(PoolCreateInfoError::UnusualBlockSize(block_size));
+        }
+
+        let (min, max) = (self.inner.minBlockCount, self.inner.maxBlockCount);
+        if max != 0 && min > max {
+            return Err(PoolCreateInfoError::MinBlockCountAboveMax { min, max });
+        }
+
+        let alignment = self.inner.minAllocationAlignment;
+        if alignment != 0 && !alignment.is_power_of_two() {
+            return Err(PoolCreateInfoError::MinAlignmentNotPowerOfTwo(alignment));
+        }
+
+        Ok(())
+    }
+}
+
+enum PoolResource<'a> {
+    Buffer(&'a vk::BufferCreateInfo),
+    Image(&'a vk::ImageCreateInfo),
+}
+
+/// Builds a [`PoolCreateInfo`] whose memory type is chosen by VMA from an example resource.
+/// Without one, the allocation create info must not use an AUTO memory usage, which is the
+/// default.
+pub struct PoolBuilder<'a> {
+    allocator: &'a Allocator,
+    resource: Option<PoolResource<'a>>,
+    allocation_create_info: AllocationCreateInfo,
+    create_info: PoolCreateInfo,
+}
+
+impl<'a> PoolBuilder<'a> {
+    pub fn with_buffer_info(mut self, buffer_create_info: &'a vk::BufferCreateInfo) -> Self {
+        self.resource = Some(PoolResource::Buffer(buffer_create_info));
+        self
+    }
+
+    pub fn with_image_info(mut self, image_create_info: &'a vk::ImageCreateInfo) -> Self {
+        self.resource = Some(PoolResource::Image(image_create_info));
+        self
+    }
+
+    pub fn with_allocation_create_info(
+        mut self,
+        allocation_create_info: AllocationCreateInfo,
+    ) -> Self {
+        self.allocation_create_info = allocation_create_info;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: PoolCreateFlags) -> Self {
+        self.create_info = self.create_info.with_flags(flags);
+        self
+    }
+
+    pub fn with_block_size(mut self, size: u64) -> Self {
+        self.create_info = self.create_info.with_block_size(size);
+        self
+    }
+
+    pub fn with_min_block_count(mut self, count: usize) -> Self {
+        self.create_info = self.create_info.with_min_block_count(count);
+        self
+    }
+
+    pub fn with_max_block_count(mut self, count: usize) -> Self {
+        self.create_info = self.create_info.with_max_block_count(count);
+        self
+    }
+
+    pub fn with_min_allocation_alignment(mut self, alignment: u64) -> Self {
+        self.create_info = self.create_info.with_min_allocation_alignment(alignment);
+        self
+    }
+
+    /// Fails with [`Error::MissingResourceInfo`] if no resource was given and the memory usage is
+    /// AUTO, which VMA cannot resolve from memory type bits alone.
+    pub fn build(self) -> Result<PoolCreateInfo, Error> {
+        let memory_type_index = match self.resource {
+            Some(PoolResource::Buffer(info)) => self
+                .allocator
+                .find_memory_type_index_for_buffer_info(info, &self.allocation_create_info)?,
+            Some(PoolResource::Image(info)) => self
+                .allocator
+                .find_memory_type_index_for_image_info(info, &self.allocation_create_info)?,
+            None if self.allocation_create_info.is_auto() => {
+                return Err(Error::MissingResourceInfo);
+            }
+            None => self
+                .allocator
+                .find_memory_type_index(u32::MAX, &self.allocation_create_info)?,
+        };
+
+        Ok(self.create_info.with_memory_type_index(memory_type_index))
+    }
+}
+
+/// Two pools of the same memory type, one for linear resources and one for optimal-tiling
+/// images. Keeping them apart is the usual fix for aliasing corruption caused by
+/// `bufferImageGranularity`, and lets both pools skip the granularity padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TilingPools {
+    pub linear: Pool,
+    pub optimal: Pool,
+}
+
+impl TilingPools {
+    pub fn create(allocator: &Allocator, create_info: &PoolCreateInfo) -> Result<Self, Error> {
+        let create_info = create_info.clone().ignore_buffer_image_granularity();
+
+        let linear = allocator.create_pool(&create_info)?;
+        let optimal = match allocator.create_pool(&create_info) {
+            Ok(pool) => pool,
+            Err(err) => {
+                allocator.destroy_pool(linear);
+                return Err(err);
+            }
+        };
+
+        Ok(Self { linear, optimal })
+    }
+
+    pub fn destroy(self, allocator: &Allocator) {
+        allocator.destroy_pool(self.linear);
+        allocator.destroy_pool(self.optimal);
+    }
+
+    pub const fn for_buffer(&self) -> Pool {
+        self.linear
+    }
+
+    pub fn for_image(&self, image_create_info: &vk::ImageCreateInfo) -> Pool {
+        match image_create_info.as_raw().tiling {
+            vk::sys::VK_IMAGE_TILING_LINEAR => self.linear,
+            _ => self.optimal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_needs_resource_info_for_auto() {
+        // Rejected before VMA is called, so the allocator is never used.
+        let allocator = unsafe { Allocator::from_raw(std::ptr::null_mut()) };
+        assert_eq!(
+            Pool::builder(&allocator).build().err(),
+            Some(Error::MissingResourceInfo)
+        );
+
+        let info = AllocationCreateInfo::AUTO_PREFER_HOST;
+        let builder = Pool::builder(&allocator).with_allocation_create_info(info);
+        assert_eq!(builder.build().err(), Some(Error::MissingResourceInfo));
+    }
+
+    #[test]
+    fn validate_accepts_defaults() {
+        assert_eq!(PoolCreateInfo::new().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_block_size() {
+        for size in [0, MIN_POOL_BLOCK_SIZE, 1 << 20, 3 << 20, 1 << 63] {
+            let info = PoolCreateInfo::new().with_block_size(size);
+            assert_eq!(info.validate(), Ok(()), "block size {}", size);
+        }
+
+        for size in [1, 3, 256, MIN_POOL_BLOCK_SIZE - 1] {
+            let info = PoolCreateInfo::new().with_block_size(size);
+            assert_eq!(
+                info.validate(),
+                Err(PoolCreateInfoError::BlockSizeTooSmall(size))
+            );
+        }
+
+        for size in [(1 << 20) + 1, (1 << 21) - 1, u64::MAX] {
+            let info = PoolCreateInfo::new().with_block_size(size);
+            assert_eq!(
+                info.validate(),
+                Err(PoolCreateInfoError::UnusualBlockSize(size))
+            );
+        }
+    }
+
+    #[test]
+    fn validate_block_counts() {
+        let info = PoolCreateInfo::new()
+            .with_min_block_count(4)
+            .with_max_block_count(4);
+        assert_eq!(info.validate(), Ok(()));
+
+        // A max of 0 is unlimited.
+        let info = PoolCreateInfo::new().with_min_block_count(usize::MAX);
+        assert_eq!(info.validate(), Ok(()));
+
+        let info = PoolCreateInfo::new()
+            .with_min_block_count(5)
+            .with_max_block_count(4);
+        assert_eq!(
+            info.validate(),
+            Err(PoolCreateInfoError::MinBlockCountAboveMax { min: 5, max: 4 })
+        );
+    }
+
+    #[test]
+    fn validate_min_alignment() {
+        for alignment in [0, 1, 256, 1 << 63] {
+            let info = PoolCreateInfo::new().with_min_allocation_alignment(alignment);
+            assert_eq!(info.validate(), Ok(()), "alignment {}", alignment);
+        }
+
+        for alignment in [3, 257, u64::MAX] {
+            let info = PoolCreateInfo::new().with_min_allocation_alignment(alignment);
+            assert_eq!(
+                info.validate(),
+                Err(PoolCreateInfoError::MinAlignmentNotPowerOfTwo(alignment))
+            );
+        }
+    }
+}
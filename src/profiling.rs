@@ -0,0 +1,241 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+pub const MAX_MEMORY_HEAPS: usize = vk::sys::VK_MAX_MEMORY_HEAPS as usize;
+
+/// One heap of a [`ProfilingSnapshot`], laid out for C consumers.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapSnapshot {
+    pub budget: u64,
+    pub usage: u64,
+    pub block_bytes: u64,
+    pub allocation_bytes: u64,
+    pub block_count: u32,
+    pub allocation_count: u32,
+}
+
+/// Per-heap budgets and statistics in a fixed-size, C-compatible layout, cheap enough to take
+/// every frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProfilingSnapshot {
+    pub heap_count: u32,
+    pub heaps: [HeapSnapshot; MAX_MEMORY_HEAPS],
+}
+
+impl ProfilingSnapshot {
+    pub fn from_budgets(budgets: &[Budget]) -> Self {
+        let mut snapshot = Self::default();
+        for (heap, budget) in snapshot.heaps.iter_mut().zip(budgets) {
+            *heap = HeapSnapshot {
+                budget: budget.budget,
+                usage: budget.usage,
+                block_bytes: budget.statistics.block_bytes,
+                allocation_bytes: budget.statistics.allocation_bytes,
+                block_count: budget.statistics.block_count,
+                allocation_count: budget.statistics.allocation_count,
+            };
+        }
+        snapshot.heap_count = budgets.len().min(MAX_MEMORY_HEAPS) as u32;
+        snapshot
+    }
+
+    pub fn heaps(&self) -> &[HeapSnapshot] {
+        &self.heaps[..self.heap_count as usize]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AllocationEventKind {
+    Created,
+    Destroyed,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AllocationEvent<'a> {
+    pub kind: AllocationEventKind,
+    pub allocation: Allocation,
+    pub id: Option<u64>,
+    pub size: u64,
+    pub memory_type: u32,
+    pub name: Option<&'a str>,
+}
+
+/// Receives what an allocator reports to profilers. Called with the profiler registry locked, so
+/// implementations must not create or destroy allocations or change sinks.
+pub trait ProfilerSink: Send {
+    fn snapshot(&mut self, allocator: Allocator, snapshot: &ProfilingSnapshot);
+
+    fn allocation_event(&mut self, _allocator: Allocator, _event: &AllocationEvent) {}
+}
+
+struct Profiler {
+    sink: Box<dyn ProfilerSink>,
+    interval: Duration,
+    last_snapshot: Option<Instant>,
+}
+
+static PROFILERS: Mutex<BTreeMap<Allocator, Profiler>> = Mutex::new(BTreeMap::new());
+
+/// Allocators with a sink, so allocations without one skip the lock and the info query.
+static PROFILED_ALLOCATORS: AtomicUsize = AtomicUsize::new(0);
+
+fn profilers() -> MutexGuard<'static, BTreeMap<Allocator, Profiler>> {
+    PROFILERS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn any_profiled() -> bool {
+    PROFILED_ALLOCATORS.load(Ordering::Relaxed) != 0
+}
+
+/// The event is put together before the registry is locked, so the info and id queries do not
+/// run under it.
+pub(crate) fn allocation_event(
+    allocator: Allocator,
+    kind: AllocationEventKind,
+    allocation: Allocation,
+) {
+    if !any_profiled() {
+        return;
+    }
+
+    let info = allocator.get_allocation_info(allocation);
+    let name = info.name();
+    let event = AllocationEvent {
+        kind,
+        allocation,
+        id: allocator.allocation_id(allocation),
+        size: info.size(),
+        memory_type: info.memory_type(),
+        name: name.as_deref(),
+    };
+
+    if let Some(profiler) = profilers().get_mut(&allocator) {
+        profiler.sink.allocation_event(allocator, &event);
+    }
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    if !any_profiled() {
+        return;
+    }
+    if profilers().remove(&allocator).is_some() {
+        PROFILED_ALLOCATORS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Allocator {
+    /// Reports snapshots to `sink` at most every `interval` from [`Self::profile`], and every
+    /// allocation created or destroyed through the checked paths as it happens. Replaces any
+    /// previous sink.
+    pub fn set_profiler_sink(&self, sink: impl ProfilerSink + 'static, interval: Duration) {
+        let profiler = Profiler {
+            sink: Box::new(sink),
+            interval,
+            last_snapshot: None,
+        };
+        if profilers().insert(*self, profiler).is_none() {
+            PROFILED_ALLOCATORS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn clear_profiler_sink(&self) {
+        forget_allocator(*self);
+    }
+
+    pub fn profiling_snapshot(&self) -> ProfilingSnapshot {
+        ProfilingSnapshot::from_budgets(&self.get_heap_budgets())
+    }
+
+    /// Sends a snapshot to the sink if its interval has passed. Meant to be called once per frame.
+    pub fn profile(&self) {
+        let mut profilers = profilers();
+        let Some(profiler) = profilers.get_mut(self) else {
+            return;
+        };
+
+        let now = Instant::now();
+        if profiler
+            .last_snapshot
+            .is_some_and(|last| now.duration_since(last) < profiler.interval)
+        {
+            return;
+        }
+        profiler.last_snapshot = Some(now);
+        profiler.sink.snapshot(*self, &self.profiling_snapshot());
+    }
+}
+
+/// Plots usage and budget per heap and reports allocations as Tracy memory events in a pool
+/// named "vma".
+#[cfg(feature = "tracy")]
+#[derive(Default)]
+pub struct TracySink {
+    plots: Vec<(tracy_client::PlotName, tracy_client::PlotName)>,
+}
+
+#[cfg(feature = "tracy")]
+impl TracySink {
+    const POOL_NAME: &'static std::ffi::CStr = c"vma";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "tracy")]
+impl ProfilerSink for TracySink {
+    fn snapshot(&mut self, _allocator: Allocator, snapshot: &ProfilingSnapshot) {
+        let Some(client) = tracy_client::Client::running() else {
+            return;
+        };
+
+        // Plot names must live forever, so they are leaked once per heap.
+        while self.plots.len() < snapshot.heaps().len() {
+            let index = self.plots.len();
+            self.plots.push((
+                tracy_client::PlotName::new_leak(format!("vma heap {} usage", index)),
+                tracy_client::PlotName::new_leak(format!("vma heap {} budget", index)),
+            ));
+        }
+
+        for (heap, (usage, budget)) in snapshot.heaps().iter().zip(&self.plots) {
+            client.plot(*usage, heap.usage as f64);
+            client.plot(*budget, heap.budget as f64);
+        }
+    }
+
+    fn allocation_event(&mut self, _allocator: Allocator, event: &AllocationEvent) {
+        if tracy_client::Client::running().is_none() {
+            return;
+        }
+
+        let ptr = event.allocation.as_raw() as *const std::ffi::c_void;
+        unsafe {
+            match event.kind {
+                AllocationEventKind::Created => {
+                    tracy_client::sys::___tracy_emit_memory_alloc_named(
+                        ptr,
+                        event.size as usize,
+                        0,
+                        Self::POOL_NAME.as_ptr(),
+                    )
+                }
+                AllocationEventKind::Destroyed => {
+                    tracy_client::sys::___tracy_emit_memory_free_named(
+                        ptr,
+                        0,
+                        Self::POOL_NAME.as_ptr(),
+                    )
+                }
+            }
+        }
+    }
+}
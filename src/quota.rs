@@ -0,0 +1,174 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A named memory limit for one subsystem, e.g. textures, on top of VMA's heap budgets.
+/// Resources created with the `*_in_quota` methods are charged their allocation size and must be
+/// destroyed with the matching methods to give it back.
+#[derive(Debug)]
+pub struct Quota {
+    name: String,
+    limit: u64,
+    used: AtomicU64,
+}
+
+impl Quota {
+    pub fn new(name: impl Into<String>, limit: u64) -> Self {
+        Self {
+            name: name.into(),
+            limit,
+            used: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub const fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    pub fn available(&self) -> u64 {
+        self.limit.saturating_sub(self.used())
+    }
+
+    fn try_charge(&self, bytes: u64) -> Result<(), Error> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|&total| total <= self.limit)
+            })
+            .map(|_| ())
+            .map_err(|used| Error::QuotaExceeded {
+                requested: bytes,
+                available: self.limit.saturating_sub(used),
+            })
+    }
+
+    /// Saturates at 0, so releasing more than was charged, e.g. a resource destroyed through the
+    /// wrong quota, cannot wrap `used` around to the limit.
+    fn release(&self, bytes: u64) {
+        let _ = self
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                Some(used.saturating_sub(bytes))
+            });
+    }
+}
+
+impl std::fmt::Display for Quota {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} of {}",
+            self.name,
+            ByteSize(self.used()),
+            ByteSize(self.limit)
+        )
+    }
+}
+
+impl Allocator {
+    /// Creates a buffer charged to `quota`, failing with [`Error::QuotaExceeded`] if its
+    /// allocation does not fit.
+    pub fn create_buffer_in_quota(
+        &self,
+        quota: &Quota,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Buffer, Allocation), CreateError> {
+        let raw_create_info = buffer_create_info.as_raw();
+        let request = CreateRequest::Buffer {
+            size: raw_create_info.size,
+            usage: raw_create_info.usage,
+        };
+
+        let mut info = AllocationInfo::new();
+        let (buffer, allocation) =
+            self.create_buffer(buffer_create_info, allocation_create_info, Some(&mut info))?;
+
+        if let Err(err) = quota.try_charge(info.size()) {
+            self.destroy_buffer(buffer, allocation);
+            return Err(CreateError::new(err, request, allocation_create_info));
+        }
+        Ok((buffer, allocation))
+    }
+
+    pub fn destroy_buffer_in_quota(
+        &self,
+        quota: &Quota,
+        buffer: vk::Buffer,
+        allocation: Allocation,
+    ) {
+        quota.release(self.get_allocation_info(allocation).size());
+        self.destroy_buffer(buffer, allocation);
+    }
+
+    /// Like [`Self::create_buffer_in_quota`], for images.
+    pub fn create_image_in_quota(
+        &self,
+        quota: &Quota,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<(vk::Image, Allocation), CreateError> {
+        let raw_create_info = image_create_info.as_raw();
+        let request = CreateRequest::Image {
+            extent: [
+                raw_create_info.extent.width,
+                raw_create_info.extent.height,
+                raw_create_info.extent.depth,
+            ],
+            format: raw_create_info.format,
+            usage: raw_create_info.usage,
+        };
+
+        let mut info = AllocationInfo::new();
+        let (image, allocation) =
+            self.create_image(image_create_info, allocation_create_info, Some(&mut info))?;
+
+        if let Err(err) = quota.try_charge(info.size()) {
+            self.destroy_image(image, allocation);
+            return Err(CreateError::new(err, request, allocation_create_info));
+        }
+        Ok((image, allocation))
+    }
+
+    pub fn destroy_image_in_quota(&self, quota: &Quota, image: vk::Image, allocation: Allocation) {
+        quota.release(self.get_allocation_info(allocation).size());
+        self.destroy_image(image, allocation);
+    }
+
+    /// Like [`Self::create_buffer_in_quota`], for raw memory. The size is charged up front, so
+    /// nothing is allocated if it does not fit.
+    pub fn allocate_memory_in_quota(
+        &self,
+        quota: &Quota,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Allocation, CreateError> {
+        let raw_requirements = memory_requirements.as_raw();
+        let request = CreateRequest::Memory {
+            size: raw_requirements.size,
+            alignment: raw_requirements.alignment,
+            memory_type_bits: raw_requirements.memoryTypeBits,
+        };
+
+        quota
+            .try_charge(raw_requirements.size)
+            .map_err(|err| CreateError::new(err, request, allocation_create_info))?;
+
+        self.allocate_memory(memory_requirements, allocation_create_info, None)
+            .inspect_err(|_| quota.release(raw_requirements.size))
+    }
+
+    pub fn free_memory_in_quota(&self, quota: &Quota, allocation: Allocation) {
+        quota.release(self.get_allocation_info(allocation).size());
+        self.free_memory(allocation);
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// One [`Allocator`] per device, for renderers driving several GPUs.
+#[derive(Debug, Default)]
+pub struct AllocatorRegistry {
+    allocators: Vec<Allocator>,
+}
+
+impl AllocatorRegistry {
+    pub const fn new() -> Self {
+        Self {
+            allocators: Vec::new(),
+        }
+    }
+
+    /// Creates an allocator and registers it under the create info's device. Fails with
+    /// [`Error::DeviceAlreadyRegistered`], creating nothing, if the device already has one.
+    pub fn create(&mut self, create_info: &AllocatorCreateInfo) -> Result<Allocator, Error> {
+        let device = create_info.as_raw().device;
+        if self.index_of(|info| info.device == device).is_some() {
+            return Err(Error::DeviceAlreadyRegistered);
+        }
+
+        let allocator = Allocator::create(create_info)?;
+        self.allocators.push(allocator);
+        Ok(allocator)
+    }
+
+    /// Registers `allocator`, returning the allocator it replaced for the same device.
+    pub fn insert(&mut self, allocator: Allocator) -> Option<Allocator> {
+        let device = allocator.raw_allocator_info().device;
+        match self
+            .allocators
+            .iter_mut()
+            .find(|registered| registered.raw_allocator_info().device == device)
+        {
+            Some(registered) => Some(std::mem::replace(registered, allocator)),
+            None => {
+                self.allocators.push(allocator);
+                None
+            }
+        }
+    }
+
+    /// Unregisters the allocator of `device` without destroying it.
+    pub fn remove(&mut self, device: vk::Device) -> Option<Allocator> {
+        let index = self.index_of(|info| info.device == device.as_raw())?;
+        Some(self.allocators.remove(index))
+    }
+
+    pub fn get(&self, device: vk::Device) -> Option<Allocator> {
+        self.index_of(|info| info.device == device.as_raw())
+            .map(|index| self.allocators[index])
+    }
+
+    /// The first allocator created for `physical_device`.
+    pub fn get_by_physical_device(&self, physical_device: vk::PhysicalDevice) -> Option<Allocator> {
+        self.index_of(|info| info.physicalDevice == physical_device.as_raw())
+            .map(|index| self.allocators[index])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Allocator> + '_ {
+        self.allocators.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.allocators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allocators.is_empty()
+    }
+
+    /// The totals of every registered allocator, summed.
+    pub fn total_statistics(&self) -> Statistics {
+        self.allocators
+            .iter()
+            .map(|allocator| allocator.calculate_statistics().total.statistics)
+            .fold(Statistics::default(), |sum, stats| Statistics {
+                block_count: sum.block_count.saturating_add(stats.block_count),
+                allocation_count: sum.allocation_count.saturating_add(stats.allocation_count),
+                block_bytes: sum.block_bytes.saturating_add(stats.block_bytes),
+                allocation_bytes: sum.allocation_bytes.saturating_add(stats.allocation_bytes),
+            })
+    }
+
+    /// The usage and budget of every heap on every device, summed.
+    pub fn total_budget(&self) -> (u64, u64) {
+        self.allocators
+            .iter()
+            .flat_map(|allocator| allocator.get_heap_budgets())
+            .fold((0, 0), |(usage, budget), heap| {
+                (
+                    usage.saturating_add(heap.usage),
+                    budget.saturating_add(heap.budget),
+                )
+            })
+    }
+
+    /// Destroys every registered allocator.
+    pub fn destroy(self) {
+        for allocator in self.allocators {
+            allocator.destroy();
+        }
+    }
+
+    fn index_of(&self, pred: impl Fn(&vma_sys::VmaAllocatorInfo) -> bool) -> Option<usize> {
+        self.allocators
+            .iter()
+            .position(|allocator| pred(&allocator.raw_allocator_info()))
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Resident {
+    priority: u32,
+    size: u64,
+    heap_index: u32,
+}
+
+/// Tracks allocations with priorities and picks which ones to evict or demote when a heap's
+/// usage goes over a fraction of its budget. Eviction itself is left to the caller.
+pub struct ResidencyManager {
+    allocator: Allocator,
+    budget_fraction: f64,
+    residents: BTreeMap<Allocation, Resident>,
+}
+
+impl ResidencyManager {
+    /// `budget_fraction` is the share of each heap's budget usage may reach, e.g. 0.9.
+    pub fn new(allocator: Allocator, budget_fraction: f64) -> Self {
+        Self {
+            allocator,
+            budget_fraction,
+            residents: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_budget_fraction(&mut self, budget_fraction: f64) {
+        self.budget_fraction = budget_fraction;
+    }
+
+    /// Starts tracking `allocation`. Lower priorities are evicted first.
+    pub fn track(&mut self, allocation: Allocation, priority: u32) {
+        let info = self.allocator.get_allocation_info(allocation);
+        let heap_index = self.allocator.raw_memory_properties().memoryTypes
+            [info.memory_type() as usize]
+            .heapIndex;
+
+        self.residents.insert(
+            allocation,
+            Resident {
+                priority,
+                size: info.size(),
+                heap_index,
+            },
+        );
+    }
+
+    pub fn set_priority(&mut self, allocation: Allocation, priority: u32) {
+        if let Some(resident) = self.residents.get_mut(&allocation) {
+            resident.priority = priority;
+        }
+    }
+
+    /// Stops tracking `allocation`, which must be done before it is freed.
+    pub fn untrack(&mut self, allocation: Allocation) {
+        self.residents.remove(&allocation);
+    }
+
+    pub fn is_tracked(&self, allocation: Allocation) -> bool {
+        self.residents.contains_key(&allocation)
+    }
+
+    /// Allocations to evict, lowest priority first, so every heap gets back under its target.
+    /// Within a priority, larger allocations come first to keep the list short.
+    pub fn evictions(&self) -> Vec<Allocation> {
+        let mut evictions = Vec::new();
+
+        for (heap_index, budget) in self.allocator.get_heap_budgets().iter().enumerate() {
+            let target = (budget.budget as f64 * self.budget_fraction) as u64;
+            let mut excess = budget.usage.saturating_sub(target);
+            if excess == 0 {
+                continue;
+            }
+
+            let mut candidates: Vec<_> = self
+                .residents
+                .iter()
+                .filter(|(_, resident)| resident.heap_index as usize == heap_index)
+                .collect();
+            candidates
+                .sort_by_key(|(_, resident)| (resident.priority, std::cmp::Reverse(resident.size)));
+
+            for (&allocation, resident) in candidates {
+                if excess == 0 {
+                    break;
+                }
+                evictions.push(allocation);
+                excess = excess.saturating_sub(resident.size);
+            }
+        }
+
+        evictions
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::macros::*;
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopedResource {
+    Buffer(vk::Buffer),
+    Image(vk::Image),
+    Memory,
+}
+
+/// An arena over a custom pool. Everything created through the scope is freed together by
+/// [`Self::reset`], which keeps the pool and its blocks for the next round, or when the scope is
+/// dropped, which also destroys the pool. The scope must not outlive its allocator.
+#[derive(Debug)]
+pub struct MemoryScope {
+    allocator: Allocator,
+    pool: Pool,
+    allocations: Vec<(ScopedResource, Allocation)>,
+}
+
+impl MemoryScope {
+    pub fn new(allocator: &Allocator, create_info: &PoolCreateInfo) -> Result<Self, Error> {
+        Ok(Self {
+            allocator: *allocator,
+            pool: allocator.create_pool(create_info)?,
+            allocations: Vec::new(),
+        })
+    }
+
+    pub const fn pool(&self) -> Pool {
+        self.pool
+    }
+
+    pub fn len(&self) -> usize {
+        self.allocations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allocations.is_empty()
+    }
+
+    /// Creates a buffer in the scope's pool, overriding any pool set in `allocation_create_info`.
+    pub fn create_buffer(
+        &mut self,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Buffer, Allocation), CreateError> {
+        let (buffer, allocation) = self.allocator.create_buffer(
+            buffer_create_info,
+            &allocation_create_info.clone().with_pool(self.pool),
+            allocation_info,
+        )?;
+        self.allocations
+            .push((ScopedResource::Buffer(buffer), allocation));
+        Ok((buffer, allocation))
+    }
+
+    pub fn create_image(
+        &mut self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<(vk::Image, Allocation), CreateError> {
+        let (image, allocation) = self.allocator.create_image(
+            image_create_info,
+            &allocation_create_info.clone().with_pool(self.pool),
+            allocation_info,
+        )?;
+        self.allocations
+            .push((ScopedResource::Image(image), allocation));
+        Ok((image, allocation))
+    }
+
+    pub fn allocate_memory(
+        &mut self,
+        memory_requirements: &vk::MemoryRequirements,
+        allocation_create_info: &AllocationCreateInfo,
+        allocation_info: Option<&mut AllocationInfo>,
+    ) -> Result<Allocation, CreateError> {
+        let allocation = self.allocator.allocate_memory(
+            memory_requirements,
+            &allocation_create_info.clone().with_pool(self.pool),
+            allocation_info,
+        )?;
+        self.allocations.push((ScopedResource::Memory, allocation));
+        Ok(allocation)
+    }
+
+    /// Frees one allocation early, along with its buffer or image. Returns `false` if it does not
+    /// belong to the scope.
+    pub fn free(&mut self, allocation: Allocation) -> bool {
+        match self.allocations.iter().position(|e| e.1 == allocation) {
+            Some(index) => {
+                // Keep the rest in creation order so `reset` still frees newest first.
+                let entry = self.allocations.remove(index);
+                self.free_entry(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Frees everything in the scope, newest first.
+    pub fn reset(&mut self) {
+        trace!(
+            pool = %self.pool,
+            count = self.allocations.len(),
+            "memory scope reset"
+        );
+        while let Some(entry) = self.allocations.pop() {
+            self.free_entry(entry);
+        }
+    }
+
+    /// Frees everything and destroys the pool, same as dropping the scope.
+    pub fn destroy(self) {
+        drop(self);
+    }
+
+    fn free_entry(&self, (resource, allocation): (ScopedResource, Allocation)) {
+        match resource {
+            ScopedResource::Buffer(buffer) => self.allocator.destroy_buffer(buffer, allocation),
+            ScopedResource::Image(image) => self.allocator.destroy_image(image, allocation),
+            ScopedResource::Memory => self.allocator.free_memory(allocation),
+        }
+    }
+}
+
+impl Drop for MemoryScope {
+    fn drop(&mut self) {
+        self.reset();
+        self.allocator.destroy_pool(self.pool);
+    }
+}
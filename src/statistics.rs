@@ -0,0 +1,516 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use vma_sys::*;
+
+/// Formats a byte count with a binary unit, e.g. `1.50 MiB`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut size = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.2} {}", size, UNITS[unit])
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Statistics {
+    pub block_count: u32,
+    pub allocation_count: u32,
+    pub block_bytes: u64,
+    pub allocation_bytes: u64,
+}
+
+impl From<VmaStatistics> for Statistics {
+    fn from(raw: VmaStatistics) -> Self {
+        Self {
+            block_count: raw.blockCount,
+            allocation_count: raw.allocationCount,
+            block_bytes: raw.blockBytes,
+            allocation_bytes: raw.allocationBytes,
+        }
+    }
+}
+
+impl Statistics {
+    /// The change from `self` to `other`.
+    pub fn diff(&self, other: &Statistics) -> StatisticsDelta {
+        // Byte counts can exceed i64, so saturate instead of wrapping.
+        let bytes = |before: u64, after: u64| {
+            (after as i128 - before as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        };
+
+        StatisticsDelta {
+            block_count: other.block_count as i64 - self.block_count as i64,
+            allocation_count: other.allocation_count as i64 - self.allocation_count as i64,
+            block_bytes: bytes(self.block_bytes, other.block_bytes),
+            allocation_bytes: bytes(self.allocation_bytes, other.allocation_bytes),
+        }
+    }
+}
+
+impl std::fmt::Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} allocations ({}) in {} blocks ({})",
+            self.allocation_count,
+            ByteSize(self.allocation_bytes),
+            self.block_count,
+            ByteSize(self.block_bytes)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatisticsDelta {
+    pub block_count: i64,
+    pub allocation_count: i64,
+    pub block_bytes: i64,
+    pub allocation_bytes: i64,
+}
+
+impl StatisticsDelta {
+    pub const fn is_zero(&self) -> bool {
+        self.block_count == 0
+            && self.allocation_count == 0
+            && self.block_bytes == 0
+            && self.allocation_bytes == 0
+    }
+}
+
+impl std::fmt::Display for StatisticsDelta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = |value: i64| if value < 0 { '-' } else { '+' };
+        write!(
+            f,
+            "{:+} allocations ({}{}), {:+} blocks ({}{})",
+            self.allocation_count,
+            sign(self.allocation_bytes),
+            ByteSize(self.allocation_bytes.unsigned_abs()),
+            self.block_count,
+            sign(self.block_bytes),
+            ByteSize(self.block_bytes.unsigned_abs())
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DetailedStatistics {
+    pub statistics: Statistics,
+    pub unused_range_count: u32,
+    pub allocation_size_min: u64,
+    pub allocation_size_max: u64,
+    pub unused_range_size_min: u64,
+    pub unused_range_size_max: u64,
+}
+
+impl From<VmaDetailedStatistics> for DetailedStatistics {
+    fn from(raw: VmaDetailedStatistics) -> Self {
+        Self {
+            statistics: raw.statistics.into(),
+            unused_range_count: raw.unusedRangeCount,
+            allocation_size_min: raw.allocationSizeMin,
+            allocation_size_max: raw.allocationSizeMax,
+            unused_range_size_min: raw.unusedRangeSizeMin,
+            unused_range_size_max: raw.unusedRangeSizeMax,
+        }
+    }
+}
+
+impl std::fmt::Display for DetailedStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.statistics)?;
+        // The min/max fields are only meaningful when there is something to measure.
+        if self.statistics.allocation_count > 0 {
+            write!(
+                f,
+                ", allocations {}..{}",
+                ByteSize(self.allocation_size_min),
+                ByteSize(self.allocation_size_max)
+            )?;
+        }
+        if self.unused_range_count > 0 {
+            write!(
+                f,
+                ", {} unused ranges {}..{}",
+                self.unused_range_count,
+                ByteSize(self.unused_range_size_min),
+                ByteSize(self.unused_range_size_max)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TotalStatistics {
+    pub memory_types: Vec<DetailedStatistics>,
+    pub memory_heaps: Vec<DetailedStatistics>,
+    pub total: DetailedStatistics,
+}
+
+impl TotalStatistics {
+    pub(crate) fn from_raw(
+        raw: &VmaTotalStatistics,
+        memory_type_count: usize,
+        memory_heap_count: usize,
+    ) -> Self {
+        Self {
+            memory_types: raw.memoryType[..memory_type_count]
+                .iter()
+                .map(|&stats| stats.into())
+                .collect(),
+            memory_heaps: raw.memoryHeap[..memory_heap_count]
+                .iter()
+                .map(|&stats| stats.into())
+                .collect(),
+            total: raw.total.into(),
+        }
+    }
+
+    /// The change from `self` to `other`, e.g. between two frames. Both must come from the same
+    /// allocator.
+    pub fn diff(&self, other: &TotalStatistics) -> TotalStatisticsDelta {
+        let diff_all = |before: &[DetailedStatistics], after: &[DetailedStatistics]| {
+            before
+                .iter()
+                .zip(after)
+                .map(|(before, after)| before.statistics.diff(&after.statistics))
+                .collect()
+        };
+
+        TotalStatisticsDelta {
+            memory_types: diff_all(&self.memory_types, &other.memory_types),
+            memory_heaps: diff_all(&self.memory_heaps, &other.memory_heaps),
+            total: self.total.statistics.diff(&other.total.statistics),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TotalStatisticsDelta {
+    pub memory_types: Vec<StatisticsDelta>,
+    pub memory_heaps: Vec<StatisticsDelta>,
+    pub total: StatisticsDelta,
+}
+
+impl TotalStatisticsDelta {
+    pub fn is_zero(&self) -> bool {
+        self.total.is_zero()
+            && self.memory_types.iter().all(StatisticsDelta::is_zero)
+            && self.memory_heaps.iter().all(StatisticsDelta::is_zero)
+    }
+}
+
+impl std::fmt::Display for TotalStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "total: {}", self.total)?;
+        for (index, heap) in self.memory_heaps.iter().enumerate() {
+            writeln!(f, "heap {}: {}", index, heap)?;
+        }
+        for (index, memory_type) in self.memory_types.iter().enumerate() {
+            writeln!(f, "type {}: {}", index, memory_type)?;
+        }
+        Ok(())
+    }
+}
+
+/// How scattered the free space of some blocks is, from their [`DetailedStatistics`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fragmentation {
+    pub block_bytes: u64,
+    pub unused_bytes: u64,
+    pub unused_range_count: u32,
+    pub largest_unused_range: u64,
+}
+
+impl Fragmentation {
+    pub fn from_statistics(stats: &DetailedStatistics) -> Self {
+        Self {
+            block_bytes: stats.statistics.block_bytes,
+            unused_bytes: stats
+                .statistics
+                .block_bytes
+                .saturating_sub(stats.statistics.allocation_bytes),
+            unused_range_count: stats.unused_range_count,
+            largest_unused_range: match stats.unused_range_count {
+                0 => 0,
+                _ => stats.unused_range_size_max,
+            },
+        }
+    }
+
+    /// The share of free space outside the largest free range: 0 when it is all contiguous,
+    /// approaching 1 as it splinters into small ranges.
+    pub fn ratio(&self) -> f64 {
+        if self.unused_bytes == 0 {
+            return 0.0;
+        }
+        // Clamped, as the largest range and the byte counts are read from separate statistics.
+        (1.0 - self.largest_unused_range as f64 / self.unused_bytes as f64).clamp(0.0, 1.0)
+    }
+
+    /// The share of block memory not in use.
+    pub fn unused_ratio(&self) -> f64 {
+        if self.block_bytes == 0 {
+            return 0.0;
+        }
+        self.unused_bytes as f64 / self.block_bytes as f64
+    }
+}
+
+impl std::fmt::Display for Fragmentation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.0}% fragmented, {} free in {} ranges (largest {})",
+            self.ratio() * 100.0,
+            ByteSize(self.unused_bytes),
+            self.unused_range_count,
+            ByteSize(self.largest_unused_range)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FragmentationReport {
+    pub memory_types: Vec<Fragmentation>,
+    pub memory_heaps: Vec<Fragmentation>,
+    pub total: Fragmentation,
+}
+
+impl FragmentationReport {
+    pub fn from_statistics(stats: &TotalStatistics) -> Self {
+        Self {
+            memory_types: stats
+                .memory_types
+                .iter()
+                .map(Fragmentation::from_statistics)
+                .collect(),
+            memory_heaps: stats
+                .memory_heaps
+                .iter()
+                .map(Fragmentation::from_statistics)
+                .collect(),
+            total: Fragmentation::from_statistics(&stats.total),
+        }
+    }
+
+    /// Memory types whose fragmentation ratio is at least `threshold`, e.g. 0.5.
+    pub fn fragmented_memory_types(&self, threshold: f64) -> impl Iterator<Item = u32> + '_ {
+        self.memory_types
+            .iter()
+            .enumerate()
+            .filter(move |(_, fragmentation)| fragmentation.ratio() >= threshold)
+            .map(|(index, _)| index as u32)
+    }
+
+    /// Whether a defragmentation pass is likely worth it, i.e. any memory type is at least
+    /// `threshold` fragmented.
+    pub fn should_defragment(&self, threshold: f64) -> bool {
+        self.fragmented_memory_types(threshold).next().is_some()
+    }
+}
+
+impl std::fmt::Display for FragmentationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "total: {}", self.total)?;
+        for (index, heap) in self.memory_heaps.iter().enumerate() {
+            writeln!(f, "heap {}: {}", index, heap)?;
+        }
+        for (index, memory_type) in self.memory_types.iter().enumerate() {
+            writeln!(f, "type {}: {}", index, memory_type)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Budget {
+    pub statistics: Statistics,
+    pub usage: u64,
+    pub budget: u64,
+}
+
+impl From<VmaBudget> for Budget {
+    fn from(raw: VmaBudget) -> Self {
+        Self {
+            statistics: raw.statistics.into(),
+            usage: raw.usage,
+            budget: raw.budget,
+        }
+    }
+}
+
+impl std::fmt::Display for Budget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} used, {}",
+            ByteSize(self.usage),
+            ByteSize(self.budget),
+            self.statistics
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_size_display() {
+        let cases = [
+            (0, "0 B"),
+            (1, "1 B"),
+            (1023, "1023 B"),
+            (1024, "1.00 KiB"),
+            (1536, "1.50 KiB"),
+            (1 << 20, "1.00 MiB"),
+            (1 << 30, "1.00 GiB"),
+            (1 << 40, "1.00 TiB"),
+            (1 << 50, "1.00 PiB"),
+            (1 << 60, "1.00 EiB"),
+            (u64::MAX, "16.00 EiB"),
+        ];
+        for (bytes, expected) in cases {
+            assert_eq!(ByteSize(bytes).to_string(), expected);
+        }
+    }
+
+    fn statistics(allocation_count: u32, allocation_bytes: u64) -> Statistics {
+        Statistics {
+            block_count: 1,
+            allocation_count,
+            block_bytes: 1 << 20,
+            allocation_bytes,
+        }
+    }
+
+    #[test]
+    fn diff_of_equal_statistics_is_zero() {
+        let stats = statistics(3, 4096);
+        assert!(stats.diff(&stats).is_zero());
+        assert!(Statistics::default().diff(&Statistics::default()).is_zero());
+    }
+
+    #[test]
+    fn diff_is_signed() {
+        let (before, after) = (statistics(3, 4096), statistics(1, 1024));
+        let delta = before.diff(&after);
+        assert_eq!(delta.allocation_count, -2);
+        assert_eq!(delta.allocation_bytes, -3072);
+        assert_eq!(after.diff(&before).allocation_bytes, 3072);
+    }
+
+    #[test]
+    fn diff_saturates_byte_overflow() {
+        let (empty, full) = (statistics(0, 0), statistics(u32::MAX, u64::MAX));
+        let delta = empty.diff(&full);
+        assert_eq!(delta.allocation_count, u32::MAX as i64);
+        assert_eq!(delta.allocation_bytes, i64::MAX);
+        assert_eq!(full.diff(&empty).allocation_bytes, i64::MIN);
+    }
+
+    #[test]
+    fn total_diff_of_empty_statistics() {
+        let empty = TotalStatistics {
+            memory_types: Vec::new(),
+            memory_heaps: Vec::new(),
+            total: DetailedStatistics::default(),
+        };
+        let delta = empty.diff(&empty);
+        assert!(delta.is_zero());
+        assert!(delta.memory_types.is_empty());
+        assert!(delta.memory_heaps.is_empty());
+    }
+
+    #[test]
+    fn total_diff_per_memory_type() {
+        let detailed = |allocation_count| DetailedStatistics {
+            statistics: statistics(allocation_count, 256 * allocation_count as u64),
+            ..Default::default()
+        };
+        let totals = |counts: [u32; 2]| TotalStatistics {
+            memory_types: counts.map(detailed).to_vec(),
+            memory_heaps: vec![detailed(counts[0] + counts[1])],
+            total: detailed(counts[0] + counts[1]),
+        };
+
+        let delta = totals([1, 2]).diff(&totals([1, 5]));
+        assert!(!delta.is_zero());
+        assert!(delta.memory_types[0].is_zero());
+        assert_eq!(delta.memory_types[1].allocation_count, 3);
+        assert_eq!(delta.memory_heaps[0].allocation_bytes, 768);
+        assert_eq!(delta.total.allocation_count, 3);
+    }
+
+    fn fragmentation(unused_bytes: u64, largest_unused_range: u64) -> Fragmentation {
+        Fragmentation {
+            block_bytes: 1 << 20,
+            unused_bytes,
+            unused_range_count: 1,
+            largest_unused_range,
+        }
+    }
+
+    #[test]
+    fn ratio_of_empty_statistics_is_zero() {
+        let empty = Fragmentation::from_statistics(&DetailedStatistics::default());
+        assert_eq!(empty, Fragmentation::default());
+        assert_eq!(empty.ratio(), 0.0);
+        assert_eq!(empty.unused_ratio(), 0.0);
+    }
+
+    #[test]
+    fn ratio_of_contiguous_free_space_is_zero() {
+        assert_eq!(fragmentation(4096, 4096).ratio(), 0.0);
+    }
+
+    #[test]
+    fn ratio_grows_as_free_space_splinters() {
+        assert_eq!(fragmentation(4096, 1024).ratio(), 0.75);
+        assert!(fragmentation(u64::MAX, 1).ratio() > 0.999);
+    }
+
+    #[test]
+    fn ratio_is_clamped() {
+        assert_eq!(fragmentation(1024, 4096).ratio(), 0.0);
+        assert_eq!(fragmentation(u64::MAX, u64::MAX).ratio(), 0.0);
+    }
+
+    #[test]
+    fn from_statistics_ignores_stale_range_sizes() {
+        let stats = DetailedStatistics {
+            statistics: statistics(1, 1 << 20),
+            unused_range_count: 0,
+            unused_range_size_max: 4096,
+            ..Default::default()
+        };
+        let fragmentation = Fragmentation::from_statistics(&stats);
+        assert_eq!(fragmentation.unused_bytes, 0);
+        assert_eq!(fragmentation.largest_unused_range, 0);
+        assert_eq!(fragmentation.ratio(), 0.0);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::ops::Deref;
+use std::sync::Mutex;
+use vma_sys::*;
+
+/// A range of a [`SubAllocator`]. Not `Copy`, as [`SubAllocator::free`] consumes it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SubAllocation {
+    block: VmaVirtualBlock,
+    allocation: VirtualAllocation,
+    offset: u64,
+    size: u64,
+}
+
+// The block is only compared to find the owning suballocator, never used.
+unsafe impl Send for SubAllocation {}
+unsafe impl Sync for SubAllocation {}
+
+impl SubAllocation {
+    pub const fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// A device buffer carved into `(offset, size)` ranges by a [`VirtualBlock`].
+pub struct SubAllocator {
+    allocator: Allocator,
+    buffer: vk::Buffer,
+    allocation: Allocation,
+    size: u64,
+    block: Mutex<VirtualBlock>,
+}
+
+impl SubAllocator {
+    pub fn create(
+        allocator: &Allocator,
+        buffer_create_info: &vk::BufferCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+    ) -> Result<Self, Error> {
+        let size = buffer_create_info.as_raw().size;
+        let block = VirtualBlock::create(&VirtualBlockCreateInfo::new().with_size(size))?;
+        let (buffer, allocation) =
+            allocator.create_buffer(buffer_create_info, allocation_create_info, None)?;
+
+        Ok(Self {
+            allocator: *allocator,
+            buffer,
+            allocation,
+            size,
+            block: Mutex::new(block),
+        })
+    }
+
+    /// Destroys the buffer, freeing every range still allocated from it.
+    pub fn destroy(self) {
+        drop(self.block);
+        self.allocator.destroy_buffer(self.buffer, self.allocation);
+    }
+
+    pub const fn buffer(&self) -> vk::Buffer {
+        self.buffer
+    }
+
+    pub const fn allocation(&self) -> Allocation {
+        self.allocation
+    }
+
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn allocate(&self, size: u64, alignment: u64) -> Result<SubAllocation, Error> {
+        let create_info = VirtualAllocationCreateInfo::new()
+            .with_size(size)
+            .with_alignment(alignment);
+
+        let mut block = self.block();
+        let (allocation, offset) = block.allocate(&create_info)?;
+
+        Ok(SubAllocation {
+            block: block.as_raw(),
+            allocation,
+            offset,
+            size,
+        })
+    }
+
+    /// Like [`SubAllocator::allocate`], but the range is freed when the returned guard drops.
+    pub fn allocate_scoped(
+        &self,
+        size: u64,
+        alignment: u64,
+    ) -> Result<SubAllocationGuard<'_>, Error> {
+        Ok(SubAllocationGuard {
+            suballocator: self,
+            suballocation: Some(self.allocate(size, alignment)?),
+        })
+    }
+
+    /// Panics if `suballocation` came from another suballocator.
+    pub fn free(&self, suballocation: SubAllocation) {
+        let mut block = self.block();
+        assert!(
+            suballocation.block == block.as_raw(),
+            "range freed through a suballocator it was not allocated from"
+        );
+        block.free(suballocation.allocation);
+    }
+
+    pub fn clear(&self) {
+        self.block().clear();
+    }
+
+    fn block(&self) -> std::sync::MutexGuard<'_, VirtualBlock> {
+        self.block.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+pub struct SubAllocationGuard<'a> {
+    suballocator: &'a SubAllocator,
+    // Only taken by into_inner and drop.
+    suballocation: Option<SubAllocation>,
+}
+
+impl SubAllocationGuard<'_> {
+    /// Releases the range from the guard without freeing it.
+    pub fn into_inner(mut self) -> SubAllocation {
+        self.suballocation.take().unwrap()
+    }
+}
+
+impl Deref for SubAllocationGuard<'_> {
+    type Target = SubAllocation;
+
+    fn deref(&self) -> &Self::Target {
+        self.suballocation.as_ref().unwrap()
+    }
+}
+
+impl Drop for SubAllocationGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(suballocation) = self.suballocation.take() {
+            self.suballocator.free(suballocation);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// A texture and the staging buffer holding its pixels, waiting for the caller to record the
+/// copies. The image must be in `TRANSFER_DST_OPTIMAL` when they execute. Call
+/// [`StagedTexture::finish`] once they have completed to free the staging buffer.
+#[derive(Debug)]
+pub struct StagedTexture {
+    pub image: vk::Image,
+    pub allocation: Allocation,
+    pub staging_buffer: vk::Buffer,
+    pub staging_allocation: Allocation,
+    /// One region per mip level, covering all array layers, for `vkCmdCopyBufferToImage`.
+    pub regions: Vec<vk::sys::VkBufferImageCopy>,
+}
+
+impl StagedTexture {
+    pub fn finish(self, allocator: &Allocator) -> (vk::Image, Allocation) {
+        allocator.destroy_buffer(self.staging_buffer, self.staging_allocation);
+        (self.image, self.allocation)
+    }
+}
+
+impl Allocator {
+    /// Creates a color image and a staging buffer filled with `data`. `mip_offsets` holds the
+    /// byte offset of each mip level in `data`, with the layers of a level tightly packed at
+    /// `bytes_per_texel`, so block-compressed formats are not supported. `TRANSFER_DST` is added
+    /// to the image usage.
+    ///
+    /// Fails with [`Error::MipLevelMismatch`] unless there is one offset per mip level, and with
+    /// [`Error::OutOfBounds`] if a level does not fit in `data`.
+    pub fn create_texture(
+        &self,
+        image_create_info: &vk::ImageCreateInfo,
+        allocation_create_info: &AllocationCreateInfo,
+        data: &[u8],
+        mip_offsets: &[u64],
+        bytes_per_texel: u64,
+    ) -> Result<StagedTexture, Error> {
+        let mut raw_create_info = *image_create_info.as_raw();
+        if mip_offsets.len() != raw_create_info.mipLevels as usize {
+            return Err(Error::MipLevelMismatch {
+                levels: raw_create_info.mipLevels,
+                offsets: mip_offsets.len(),
+            });
+        }
+
+        for (level, &offset) in mip_offsets.iter().enumerate() {
+            let extent = mip_extent(raw_create_info.extent, level);
+            let level_size = [extent.width, extent.height, extent.depth]
+                .into_iter()
+                .chain([raw_create_info.arrayLayers])
+                .try_fold(bytes_per_texel, |size, n| size.checked_mul(n as u64));
+
+            let fits = level_size
+                .and_then(|size| offset.checked_add(size))
+                .is_some_and(|end| end <= data.len() as u64);
+            if !fits {
+                return Err(Error::OutOfBounds {
+                    offset,
+                    len: level_size.unwrap_or(u64::MAX),
+                    size: data.len() as u64,
+                });
+            }
+        }
+        raw_create_info.usage |= vk::sys::VK_IMAGE_USAGE_TRANSFER_DST_BIT as u32;
+
+        let (image, allocation) = self.create_image(
+            &vk::ImageCreateInfo::from_raw(raw_create_info),
+            allocation_create_info,
+            None,
+        )?;
+
+        let (staging_buffer, staging_allocation) = match self.create_staging_buffer(data) {
+            Ok(staging) => staging,
+            Err(err) => {
+                self.destroy_image(image, allocation);
+                return Err(err);
+            }
+        };
+
+        let regions = mip_offsets
+            .iter()
+            .enumerate()
+            .map(|(level, &offset)| vk::sys::VkBufferImageCopy {
+                bufferOffset: offset,
+                bufferRowLength: 0,
+                bufferImageHeight: 0,
+                imageSubresource: vk::sys::VkImageSubresourceLayers {
+                    aspectMask: vk::sys::VK_IMAGE_ASPECT_COLOR_BIT as u32,
+                    mipLevel: level as u32,
+                    baseArrayLayer: 0,
+                    layerCount: raw_create_info.arrayLayers,
+                },
+                imageOffset: vk::sys::VkOffset3D { x: 0, y: 0, z: 0 },
+                imageExtent: mip_extent(raw_create_info.extent, level),
+            })
+            .collect();
+
+        Ok(StagedTexture {
+            image,
+            allocation,
+            staging_buffer,
+            staging_allocation,
+            regions,
+        })
+    }
+
+    fn create_staging_buffer(&self, data: &[u8]) -> Result<(vk::Buffer, Allocation), Error> {
+        let buffer_create_info = vk::BufferCreateInfo::from_raw(vk::sys::VkBufferCreateInfo {
+            sType: vk::sys::VK_STRUCTURE_TYPE_BUFFER_CREATE_INFO,
+            pNext: std::ptr::null(),
+            flags: 0,
+            size: data.len() as u64,
+            usage: vk::sys::VK_BUFFER_USAGE_TRANSFER_SRC_BIT as u32,
+            sharingMode: vk::sys::VK_SHARING_MODE_EXCLUSIVE,
+            queueFamilyIndexCount: 0,
+            pQueueFamilyIndices: std::ptr::null(),
+        });
+        let allocation_create_info = AllocationCreateInfo::AUTO.with_creation_flags(
+            AllocationCreateFlags::HOST_ACCESS_SEQUENTIAL_WRITE | AllocationCreateFlags::MAPPED,
+        );
+
+        let mut info = AllocationInfo::new();
+        let (buffer, allocation) = self.create_buffer(
+            &buffer_create_info,
+            &allocation_create_info,
+            Some(&mut info),
+        )?;
+
+        let mapped = info.mapped_data().cast::<u8>();
+        if mapped.is_null() {
+            self.destroy_buffer(buffer, allocation);
+            return Err(Error::MemoryMapFailed);
+        }
+        unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), mapped, data.len()) };
+
+        if let Err(err) = self.flush_allocation(allocation, 0, vk::sys::VK_WHOLE_SIZE) {
+            self.destroy_buffer(buffer, allocation);
+            return Err(err);
+        }
+
+        Ok((buffer, allocation))
+    }
+}
+
+fn mip_extent(extent: vk::sys::VkExtent3D, level: usize) -> vk::sys::VkExtent3D {
+    let shrink = |size: u32| size.checked_shr(level as u32).unwrap_or(0).max(1);
+    vk::sys::VkExtent3D {
+        width: shrink(extent.width),
+        height: shrink(extent.height),
+        depth: shrink(extent.depth),
+    }
+}
@@ -0,0 +1,164 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TrackedObject {
+    Allocation(Allocation),
+    Pool(Pool),
+}
+
+impl From<Allocation> for TrackedObject {
+    fn from(allocation: Allocation) -> Self {
+        Self::Allocation(allocation)
+    }
+}
+
+impl From<Pool> for TrackedObject {
+    fn from(pool: Pool) -> Self {
+        Self::Pool(pool)
+    }
+}
+
+#[derive(Default)]
+struct Objects {
+    next_id: u64,
+    by_id: BTreeMap<u64, TrackedObject>,
+    ids: BTreeMap<TrackedObject, u64>,
+}
+
+static REGISTRY: Mutex<BTreeMap<Allocator, Objects>> = Mutex::new(BTreeMap::new());
+
+/// Allocators with tracking enabled, so untracked creates and destroys skip the lock entirely.
+static TRACKED_ALLOCATORS: AtomicUsize = AtomicUsize::new(0);
+
+fn registry() -> MutexGuard<'static, BTreeMap<Allocator, Objects>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn any_tracked() -> bool {
+    TRACKED_ALLOCATORS.load(Ordering::Relaxed) != 0
+}
+
+pub(crate) fn enable(allocator: Allocator) {
+    if let Entry::Vacant(entry) = registry().entry(allocator) {
+        entry.insert(Objects::default());
+        TRACKED_ALLOCATORS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Assigns the next id of `allocator` to `object`, if tracking is enabled. Ids start at 1 per
+/// allocator, so the same sequence of calls produces the same ids on every run.
+pub(crate) fn register(allocator: Allocator, object: TrackedObject) {
+    if !any_tracked() {
+        return;
+    }
+    let mut registry = registry();
+    let Some(objects) = registry.get_mut(&allocator) else {
+        return;
+    };
+
+    objects.next_id += 1;
+    let id = objects.next_id;
+    objects.by_id.insert(id, object);
+    objects.ids.insert(object, id);
+}
+
+pub(crate) fn unregister(allocator: Allocator, object: TrackedObject) {
+    if !any_tracked() {
+        return;
+    }
+    let mut registry = registry();
+    if let Some(objects) = registry.get_mut(&allocator) {
+        if let Some(id) = objects.ids.remove(&object) {
+            objects.by_id.remove(&id);
+        }
+    }
+}
+
+pub(crate) fn forget_allocator(allocator: Allocator) {
+    if !any_tracked() {
+        return;
+    }
+    if registry().remove(&allocator).is_some() {
+        TRACKED_ALLOCATORS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn host_bytes() -> usize {
+    let registry = registry();
+    let entry =
+        std::mem::size_of::<(u64, TrackedObject)>() + std::mem::size_of::<(TrackedObject, u64)>();
+
+    registry.len() * std::mem::size_of::<(Allocator, Objects)>()
+        + registry
+            .values()
+            .map(|objects| objects.ids.len() * entry)
+            .sum::<usize>()
+}
+
+pub(crate) fn id_of(allocator: Allocator, object: TrackedObject) -> Option<u64> {
+    if !any_tracked() {
+        return None;
+    }
+    registry().get(&allocator)?.ids.get(&object).copied()
+}
+
+pub(crate) fn object_by_id(allocator: Allocator, id: u64) -> Option<TrackedObject> {
+    if !any_tracked() {
+        return None;
+    }
+    registry().get(&allocator)?.by_id.get(&id).copied()
+}
+
+pub(crate) fn objects(allocator: Allocator) -> Vec<(u64, TrackedObject)> {
+    registry()
+        .get(&allocator)
+        .map(|objects| {
+            objects
+                .by_id
+                .iter()
+                .map(|(&id, &object)| (id, object))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_enabled_allocators_are_tracked() {
+        // The registry only uses handles as keys, so they need not be live.
+        let (tracked, untracked) = unsafe {
+            (
+                Allocator::from_raw(0x1000 as _),
+                Allocator::from_raw(0x2000 as _),
+            )
+        };
+        let allocation = unsafe { Allocation::from_raw(0x10 as _) };
+        let pool = unsafe { Pool::from_raw(0x20 as _) };
+
+        enable(tracked);
+        register(tracked, allocation.into());
+        register(tracked, pool.into());
+        register(untracked, allocation.into());
+
+        assert_eq!(id_of(tracked, allocation.into()), Some(1));
+        assert_eq!(object_by_id(tracked, 2), Some(TrackedObject::Pool(pool)));
+        assert_eq!(id_of(untracked, allocation.into()), None);
+        assert!(objects(untracked).is_empty());
+
+        unregister(tracked, allocation.into());
+        assert_eq!(objects(tracked), [(2, TrackedObject::Pool(pool))]);
+
+        forget_allocator(tracked);
+        assert_eq!(id_of(tracked, pool.into()), None);
+    }
+}
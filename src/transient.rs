@@ -0,0 +1,236 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+/// A frame-graph resource that is only alive from `first_pass` to `last_pass`, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransientResource {
+    pub size: u64,
+    pub alignment: u64,
+    pub memory_type_bits: u32,
+    pub first_pass: u32,
+    pub last_pass: u32,
+}
+
+impl TransientResource {
+    pub fn from_requirements(
+        requirements: &vk::MemoryRequirements,
+        first_pass: u32,
+        last_pass: u32,
+    ) -> Self {
+        let raw = requirements.as_raw();
+        Self {
+            size: raw.size,
+            alignment: raw.alignment,
+            memory_type_bits: raw.memoryTypeBits,
+            first_pass,
+            last_pass,
+        }
+    }
+}
+
+/// Offsets into one allocation where resources with disjoint lifetimes share memory. Allocate
+/// [`Self::memory_requirements`] and bind each resource with `create_aliasing_image2` or
+/// `create_aliasing_buffer2` at its offset.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransientLayout {
+    /// The offset of each resource, in the order they were passed to [`Self::plan`].
+    pub offsets: Vec<u64>,
+    pub size: u64,
+    pub alignment: u64,
+    pub memory_type_bits: u32,
+}
+
+impl TransientLayout {
+    /// Zero-sized resources need no memory and are placed at offset 0. Fails with
+    /// [`Error::InvalidPassRange`] if a resource ends before it starts.
+    pub fn plan(resources: &[TransientResource]) -> Result<Self, Error> {
+        if let Some(resource) = resources
+            .iter()
+            .find(|resource| resource.first_pass > resource.last_pass)
+        {
+            return Err(Error::InvalidPassRange {
+                first_pass: resource.first_pass,
+                last_pass: resource.last_pass,
+            });
+        }
+
+        // Enough for every resource side by side, so allocation only fails if that overflows.
+        let capacity = resources
+            .iter()
+            .filter(|resource| resource.size != 0)
+            .map(|resource| resource.size.saturating_add(resource.alignment.max(1) - 1))
+            .fold(0, u64::saturating_add);
+        if capacity == 0 {
+            return Ok(Self {
+                offsets: vec![0; resources.len()],
+                ..Self::default()
+            });
+        }
+
+        let mut block = VirtualBlock::create(&VirtualBlockCreateInfo::new().with_size(capacity))?;
+
+        // Largest first within a pass packs better.
+        let mut order = (0..resources.len())
+            .filter(|&index| resources[index].size != 0)
+            .collect::<Vec<_>>();
+        order.sort_by_key(|&index| {
+            let resource = &resources[index];
+            (resource.first_pass, std::cmp::Reverse(resource.size))
+        });
+
+        let mut layout = Self {
+            offsets: vec![0; resources.len()],
+            size: 0,
+            alignment: 1,
+            memory_type_bits: !0,
+        };
+        let mut live: Vec<(usize, VirtualAllocation)> = Vec::new();
+
+        for index in order {
+            let resource = &resources[index];
+
+            live.retain(|&(live_index, allocation)| {
+                let expired = resources[live_index].last_pass < resource.first_pass;
+                if expired {
+                    block.free(allocation);
+                }
+                !expired
+            });
+
+            let create_info = VirtualAllocationCreateInfo::new()
+                .with_size(resource.size)
+                .with_alignment(resource.alignment)
+                .with_flags(VirtualAllocationCreateFlags::STRATEGY_MIN_OFFSET);
+            let (allocation, offset) = block.allocate(&create_info)?;
+            live.push((index, allocation));
+
+            layout.offsets[index] = offset;
+            layout.size = layout.size.max(offset + resource.size);
+            layout.alignment = layout.alignment.max(resource.alignment);
+            layout.memory_type_bits &= resource.memory_type_bits;
+        }
+
+        block.destroy();
+        Ok(layout)
+    }
+
+    /// The bytes saved over giving every resource its own memory.
+    pub fn aliased_bytes(&self, resources: &[TransientResource]) -> u64 {
+        resources
+            .iter()
+            .map(|resource| resource.size)
+            .sum::<u64>()
+            .saturating_sub(self.size)
+    }
+
+    /// Requirements for the shared allocation. A `memoryTypeBits` of 0 means the resources have
+    /// no memory type in common and cannot alias.
+    pub fn memory_requirements(&self) -> vk::MemoryRequirements {
+        vk::MemoryRequirements::from_raw(vk::sys::VkMemoryRequirements {
+            size: self.size,
+            alignment: self.alignment,
+            memoryTypeBits: self.memory_type_bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(size: u64, alignment: u64, passes: (u32, u32)) -> TransientResource {
+        TransientResource {
+            size,
+            alignment,
+            memory_type_bits: 0b111,
+            first_pass: passes.0,
+            last_pass: passes.1,
+        }
+    }
+
+    #[test]
+    fn plan_of_nothing_is_empty() {
+        assert_eq!(TransientLayout::plan(&[]), Ok(TransientLayout::default()));
+    }
+
+    #[test]
+    fn zero_sized_resources_take_no_memory() {
+        let resources = [resource(0, 256, (0, 1)), resource(0, 1, (1, 2))];
+        let layout = TransientLayout::plan(&resources).unwrap();
+        assert_eq!(layout.offsets, [0, 0]);
+        assert_eq!(layout.size, 0);
+
+        let resources = [resource(0, 256, (0, 1)), resource(4096, 256, (0, 1))];
+        let layout = TransientLayout::plan(&resources).unwrap();
+        assert_eq!(layout.offsets, [0, 0]);
+        assert_eq!(layout.size, 4096);
+    }
+
+    #[test]
+    fn disjoint_lifetimes_alias() {
+        let resources = [resource(4096, 256, (0, 1)), resource(4096, 256, (2, 3))];
+        let layout = TransientLayout::plan(&resources).unwrap();
+        assert_eq!(layout.offsets, [0, 0]);
+        assert_eq!(layout.size, 4096);
+        assert_eq!(layout.aliased_bytes(&resources), 4096);
+    }
+
+    #[test]
+    fn overlapping_lifetimes_do_not_alias() {
+        // Lifetimes are inclusive, so sharing pass 1 is an overlap.
+        let resources = [resource(4096, 256, (0, 1)), resource(4096, 256, (1, 2))];
+        let layout = TransientLayout::plan(&resources).unwrap();
+        let (a, b) = (layout.offsets[0], layout.offsets[1]);
+        assert!(a + 4096 <= b || b + 4096 <= a);
+        assert_eq!(layout.size, 8192);
+        assert_eq!(layout.aliased_bytes(&resources), 0);
+    }
+
+    #[test]
+    fn offsets_respect_power_of_two_alignment() {
+        let resources = [
+            resource(1, 1, (0, 0)),
+            resource(1000, 1024, (0, 0)),
+            resource(1, 1 << 16, (0, 0)),
+        ];
+        let layout = TransientLayout::plan(&resources).unwrap();
+        for (resource, &offset) in resources.iter().zip(&layout.offsets) {
+            assert_eq!(offset % resource.alignment, 0);
+        }
+        assert_eq!(layout.alignment, 1 << 16);
+        assert_eq!(layout.memory_requirements().as_raw().alignment, 1 << 16);
+    }
+
+    #[test]
+    fn memory_type_bits_intersect() {
+        let mut resources = [resource(256, 1, (0, 0)), resource(256, 1, (1, 1))];
+        resources[1].memory_type_bits = 0b010;
+        let layout = TransientLayout::plan(&resources).unwrap();
+        assert_eq!(layout.memory_type_bits, 0b010);
+
+        resources[1].memory_type_bits = 0b1000;
+        let layout = TransientLayout::plan(&resources).unwrap();
+        assert_eq!(layout.memory_requirements().as_raw().memoryTypeBits, 0);
+    }
+
+    #[test]
+    fn reversed_pass_range_fails() {
+        let resources = [resource(256, 1, (0, 1)), resource(256, 1, (3, 2))];
+        assert_eq!(
+            TransientLayout::plan(&resources),
+            Err(Error::InvalidPassRange {
+                first_pass: 3,
+                last_pass: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn overflowing_resources_fail() {
+        let half = u64::MAX / 2 + 1;
+        let resources = [resource(half, 1, (0, 1)), resource(half, 1, (1, 2))];
+        assert!(TransientLayout::plan(&resources).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::macros::*;
+use crate::*;
+use vma_sys::*;
+
+/// An owned VMA virtual block. VMA does not synchronize virtual blocks, so it can move between
+/// threads but not be shared, and everything that changes it takes `&mut self`. Dropping it frees
+/// any allocations left and destroys the block.
+#[derive(Debug)]
+pub struct VirtualBlock {
+    handle: VmaVirtualBlock,
+    flags: VirtualBlockCreateFlags,
+}
+
+unsafe impl Send for VirtualBlock {}
+
+impl VirtualBlock {
+    pub fn create(create_info: &VirtualBlockCreateInfo) -> Result<Self, Error> {
+        let mut block = std::ptr::null_mut();
+
+        let result = unsafe { vmaCreateVirtualBlock(create_info.as_raw(), &mut block) };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(Self {
+            handle: block,
+            flags: VirtualBlockCreateFlags::from_bits_retain(create_info.inner.flags),
+        })
+    }
+
+    pub fn destroy(self) {
+        drop(self);
+    }
+
+    pub const fn as_raw(&self) -> VmaVirtualBlock {
+        self.handle
+    }
+
+    pub const fn flags(&self) -> VirtualBlockCreateFlags {
+        self.flags
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { vmaIsVirtualBlockEmpty(self.as_raw()) != vk::sys::VK_FALSE }
+    }
+
+    pub fn allocate(
+        &mut self,
+        create_info: &VirtualAllocationCreateInfo,
+    ) -> Result<(VirtualAllocation, u64), Error> {
+        let mut allocation = std::ptr::null_mut();
+        let mut offset = 0;
+
+        let result = unsafe {
+            vmaVirtualAllocate(
+                self.as_raw(),
+                create_info.as_raw(),
+                &mut allocation,
+                &mut offset,
+            )
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok((unsafe { VirtualAllocation::from_raw(allocation) }, offset))
+    }
+
+    pub fn free(&mut self, allocation: VirtualAllocation) {
+        unsafe { vmaVirtualFree(self.as_raw(), allocation.as_raw()) };
+    }
+
+    pub fn clear(&mut self) {
+        unsafe { vmaClearVirtualBlock(self.as_raw()) };
+    }
+
+    pub fn get_allocation_info(&self, allocation: VirtualAllocation) -> VirtualAllocationInfo {
+        let mut info = VirtualAllocationInfo::new();
+        unsafe { vmaGetVirtualAllocationInfo(self.as_raw(), allocation.as_raw(), &mut info.inner) };
+        info
+    }
+
+    pub fn set_allocation_user_data<T>(
+        &mut self,
+        allocation: VirtualAllocation,
+        user_data: *mut T,
+    ) {
+        unsafe {
+            vmaSetVirtualAllocationUserData(self.as_raw(), allocation.as_raw(), user_data.cast())
+        };
+    }
+
+    /// Splits `allocation` into `[0, at_offset)` and `[at_offset, size)`, covering the same
+    /// range as the original, whose handle is no longer valid afterwards. Both halves keep the
+    /// original's user data.
+    ///
+    /// Only blocks using the default algorithm are supported, as linear blocks do not reuse freed
+    /// ranges; linear blocks fail with [`Error::UnsupportedAlgorithm`] and are left untouched.
+    /// Panics if `at_offset` is not strictly inside the allocation.
+    pub fn split(
+        &mut self,
+        allocation: VirtualAllocation,
+        at_offset: u64,
+    ) -> Result<(VirtualAllocation, VirtualAllocation), Error> {
+        self.check_default_algorithm()?;
+
+        let info = self.get_allocation_info(allocation);
+        let (offset, size) = (info.offset(), info.size());
+        assert!(
+            at_offset > 0 && at_offset < size,
+            "split offset {} is outside the allocation of size {}",
+            at_offset,
+            size
+        );
+
+        self.free(allocation);
+
+        let head = self.allocate_at(offset, at_offset)?;
+        let tail = self.allocate_at(offset + at_offset, size - at_offset)?;
+        self.set_allocation_user_data(head, info.user_data());
+        self.set_allocation_user_data(tail, info.user_data());
+        Ok((head, tail))
+    }
+
+    /// Merges two adjacent allocations into one covering both ranges, invalidating both handles.
+    /// The result keeps the user data of the lower allocation. Returns `None`, leaving both
+    /// untouched, if they are not adjacent.
+    ///
+    /// Like [`VirtualBlock::split`], this requires the default algorithm.
+    pub fn try_merge(
+        &mut self,
+        a: VirtualAllocation,
+        b: VirtualAllocation,
+    ) -> Result<Option<VirtualAllocation>, Error> {
+        self.check_default_algorithm()?;
+
+        let (a_info, b_info) = (self.get_allocation_info(a), self.get_allocation_info(b));
+        let (first, second) = if a_info.offset() <= b_info.offset() {
+            (a_info, b_info)
+        } else {
+            (b_info, a_info)
+        };
+
+        if a == b || first.offset() + first.size() != second.offset() {
+            return Ok(None);
+        }
+
+        self.free(a);
+        self.free(b);
+
+        let merged = self.allocate_at(first.offset(), first.size() + second.size())?;
+        self.set_allocation_user_data(merged, first.user_data());
+        Ok(Some(merged))
+    }
+
+    fn check_default_algorithm(&self) -> Result<(), Error> {
+        if self
+            .flags
+            .contains(VirtualBlockCreateFlags::LINEAR_ALGORITHM)
+        {
+            return Err(Error::UnsupportedAlgorithm);
+        }
+        Ok(())
+    }
+
+    /// Allocates exactly `[offset, offset + size)`, which the caller has just freed.
+    ///
+    /// VMA cannot place an allocation at a given offset, so this allocates lowest-offset-first and
+    /// temporarily fills any free space found below `offset` until the allocation lands on it.
+    /// Every request fits in a range known to be free, so VMA cannot fail it.
+    fn allocate_at(&mut self, offset: u64, size: u64) -> Result<VirtualAllocation, Error> {
+        self.check_default_algorithm()?;
+
+        fn allocate(block: &mut VirtualBlock, size: u64) -> (VirtualAllocation, u64) {
+            let create_info = VirtualAllocationCreateInfo::new()
+                .with_size(size)
+                .with_flags(VirtualAllocationCreateFlags::STRATEGY_MIN_OFFSET);
+            block
+                .allocate(&create_info)
+                .expect("virtual allocation into a freed range failed")
+        }
+
+        let mut fillers = Vec::new();
+        let allocation = loop {
+            let (allocation, found) = allocate(self, size);
+            if found == offset {
+                break allocation;
+            }
+
+            self.free(allocation);
+            assert!(
+                found < offset,
+                "virtual range at {:#x} of size {:#x} is not free",
+                offset,
+                size
+            );
+
+            fillers.push(allocate(self, size.min(offset - found)).0);
+        };
+
+        for filler in fillers {
+            self.free(filler);
+        }
+
+        Ok(allocation)
+    }
+}
+
+impl Drop for VirtualBlock {
+    fn drop(&mut self) {
+        unsafe {
+            vmaClearVirtualBlock(self.handle);
+            vmaDestroyVirtualBlock(self.handle);
+        }
+    }
+}
+
+vma_struct!(VirtualBlockCreateInfo, VmaVirtualBlockCreateInfo, Clone);
+
+impl VirtualBlockCreateInfo {
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.inner.size = size;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: VirtualBlockCreateFlags) -> Self {
+        self.inner.flags = flags.bits();
+        self
+    }
+}
+
+vma_handle!(VirtualAllocation, VmaVirtualAllocation);
+
+vma_struct!(
+    VirtualAllocationCreateInfo,
+    VmaVirtualAllocationCreateInfo,
+    Clone
+);
+
+impl VirtualAllocationCreateInfo {
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.inner.size = size;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: u64) -> Self {
+        self.inner.alignment = alignment;
+        self
+    }
+
+    pub fn with_flags(mut self, flags: VirtualAllocationCreateFlags) -> Self {
+        self.inner.flags = flags.bits();
+        self
+    }
+
+    /// Replaces any strategy bits already set, leaving the other flags alone.
+    pub fn with_strategy(mut self, strategy: VirtualAllocationCreateFlags) -> Self {
+        let mask = VirtualAllocationCreateFlags::STRATEGY_MASK.bits();
+        self.inner.flags = (self.inner.flags & !mask) | (strategy.bits() & mask);
+        self
+    }
+
+    pub fn with_user_data<T>(mut self, user_data: *mut T) -> Self {
+        self.inner.pUserData = user_data.cast();
+        self
+    }
+}
+
+vma_struct!(VirtualAllocationInfo, VmaVirtualAllocationInfo, Clone);
+
+impl VirtualAllocationInfo {
+    pub const fn offset(&self) -> u64 {
+        self.inner.offset
+    }
+
+    pub const fn size(&self) -> u64 {
+        self.inner.size
+    }
+
+    pub const fn user_data(&self) -> *mut std::ffi::c_void {
+        self.inner.pUserData
+    }
+
+    /// # Safety
+    /// The user data must be null or point to a live `T`.
+    pub unsafe fn user_data_as<T>(&self) -> Option<&T> {
+        (self.inner.pUserData as *const T).as_ref()
+    }
+}
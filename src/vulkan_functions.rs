@@ -0,0 +1,190 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::macros::*;
+use crate::*;
+use std::ffi::CStr;
+use vma_sys::*;
+
+vma_struct!(VulkanFunctions, VmaVulkanFunctions, Clone);
+
+/// The entry points [`VulkanFunctions::load`] could not find, by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFunctions(pub Vec<&'static str>);
+
+impl std::fmt::Display for MissingFunctions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "missing Vulkan functions: {}", self.0.join(", "))
+    }
+}
+
+impl std::error::Error for MissingFunctions {}
+
+struct Loader {
+    instance: vk::sys::VkInstance,
+    device: vk::sys::VkDevice,
+    get_instance_proc_addr: unsafe extern "system" fn(
+        vk::sys::VkInstance,
+        *const std::ffi::c_char,
+    ) -> vk::sys::PFN_vkVoidFunction,
+    get_device_proc_addr: unsafe extern "system" fn(
+        vk::sys::VkDevice,
+        *const std::ffi::c_char,
+    ) -> vk::sys::PFN_vkVoidFunction,
+    missing: Vec<&'static str>,
+}
+
+impl Loader {
+    /// Returns the first of `names` that resolves. If none do and `required` is set, the first
+    /// name is reported as missing.
+    unsafe fn load<T: Copy>(
+        &mut self,
+        device_level: bool,
+        required: bool,
+        names: &[&'static CStr],
+    ) -> T {
+        assert_eq!(
+            std::mem::size_of::<T>(),
+            std::mem::size_of::<vk::sys::PFN_vkVoidFunction>()
+        );
+
+        let function = names.iter().find_map(|name| match device_level {
+            true => (self.get_device_proc_addr)(self.device, name.as_ptr()),
+            false => (self.get_instance_proc_addr)(self.instance, name.as_ptr()),
+        });
+        if function.is_none() && required {
+            self.missing.push(names[0].to_str().unwrap());
+        }
+        std::mem::transmute_copy(&function)
+    }
+}
+
+const fn version_at_least(version: vk::ApiVersion, major: u32, minor: u32) -> bool {
+    let (actual_major, actual_minor) = ((version.0 >> 22) & 0x7f, (version.0 >> 12) & 0x3ff);
+    actual_major > major || (actual_major == major && actual_minor >= minor)
+}
+
+impl VulkanFunctions {
+    /// Fills the whole table for `api_version`, taking core entry points where the version has
+    /// them and the KHR extension ones otherwise. Extension functions are optional below the
+    /// version that made them core, as they are only used when the matching allocator flags are
+    /// set.
+    ///
+    /// # Safety
+    /// The instance and device must be valid, and the two loaders must belong to them.
+    pub unsafe fn load(
+        api_version: vk::ApiVersion,
+        instance: vk::Instance,
+        device: vk::Device,
+        get_instance_proc_addr: vk::sys::PFN_vkGetInstanceProcAddr,
+        get_device_proc_addr: vk::sys::PFN_vkGetDeviceProcAddr,
+    ) -> Result<Self, MissingFunctions> {
+        let (Some(instance_loader), Some(device_loader)) =
+            (get_instance_proc_addr, get_device_proc_addr)
+        else {
+            return Err(MissingFunctions(vec![
+                "vkGetInstanceProcAddr",
+                "vkGetDeviceProcAddr",
+            ]));
+        };
+
+        let mut loader = Loader {
+            instance: instance.as_raw(),
+            device: device.as_raw(),
+            get_instance_proc_addr: instance_loader,
+            get_device_proc_addr: device_loader,
+            missing: Vec::new(),
+        };
+
+        let vulkan_1_1 = version_at_least(api_version, 1, 1);
+        let vulkan_1_3 = version_at_least(api_version, 1, 3);
+
+        let mut functions = Self::new();
+        let table = &mut functions.inner;
+        table.vkGetInstanceProcAddr = std::mem::transmute_copy(&get_instance_proc_addr);
+        table.vkGetDeviceProcAddr = std::mem::transmute_copy(&get_device_proc_addr);
+
+        table.vkGetPhysicalDeviceProperties =
+            loader.load(false, true, &[c"vkGetPhysicalDeviceProperties"]);
+        table.vkGetPhysicalDeviceMemoryProperties =
+            loader.load(false, true, &[c"vkGetPhysicalDeviceMemoryProperties"]);
+        table.vkAllocateMemory = loader.load(true, true, &[c"vkAllocateMemory"]);
+        table.vkFreeMemory = loader.load(true, true, &[c"vkFreeMemory"]);
+        table.vkMapMemory = loader.load(true, true, &[c"vkMapMemory"]);
+        table.vkUnmapMemory = loader.load(true, true, &[c"vkUnmapMemory"]);
+        table.vkFlushMappedMemoryRanges = loader.load(true, true, &[c"vkFlushMappedMemoryRanges"]);
+        table.vkInvalidateMappedMemoryRanges =
+            loader.load(true, true, &[c"vkInvalidateMappedMemoryRanges"]);
+        table.vkBindBufferMemory = loader.load(true, true, &[c"vkBindBufferMemory"]);
+        table.vkBindImageMemory = loader.load(true, true, &[c"vkBindImageMemory"]);
+        table.vkGetBufferMemoryRequirements =
+            loader.load(true, true, &[c"vkGetBufferMemoryRequirements"]);
+        table.vkGetImageMemoryRequirements =
+            loader.load(true, true, &[c"vkGetImageMemoryRequirements"]);
+        table.vkCreateBuffer = loader.load(true, true, &[c"vkCreateBuffer"]);
+        table.vkDestroyBuffer = loader.load(true, true, &[c"vkDestroyBuffer"]);
+        table.vkCreateImage = loader.load(true, true, &[c"vkCreateImage"]);
+        table.vkDestroyImage = loader.load(true, true, &[c"vkDestroyImage"]);
+        table.vkCmdCopyBuffer = loader.load(true, true, &[c"vkCmdCopyBuffer"]);
+
+        // Core in 1.1, VK_KHR_get_memory_requirements2, VK_KHR_bind_memory2 and
+        // VK_KHR_get_physical_device_properties2 before that.
+        table.vkGetBufferMemoryRequirements2KHR = loader.load(
+            true,
+            vulkan_1_1,
+            &[
+                c"vkGetBufferMemoryRequirements2",
+                c"vkGetBufferMemoryRequirements2KHR",
+            ],
+        );
+        table.vkGetImageMemoryRequirements2KHR = loader.load(
+            true,
+            vulkan_1_1,
+            &[
+                c"vkGetImageMemoryRequirements2",
+                c"vkGetImageMemoryRequirements2KHR",
+            ],
+        );
+        table.vkBindBufferMemory2KHR = loader.load(
+            true,
+            vulkan_1_1,
+            &[c"vkBindBufferMemory2", c"vkBindBufferMemory2KHR"],
+        );
+        table.vkBindImageMemory2KHR = loader.load(
+            true,
+            vulkan_1_1,
+            &[c"vkBindImageMemory2", c"vkBindImageMemory2KHR"],
+        );
+        table.vkGetPhysicalDeviceMemoryProperties2KHR = loader.load(
+            false,
+            vulkan_1_1,
+            &[
+                c"vkGetPhysicalDeviceMemoryProperties2",
+                c"vkGetPhysicalDeviceMemoryProperties2KHR",
+            ],
+        );
+
+        // Core in 1.3, VK_KHR_maintenance4 before that.
+        table.vkGetDeviceBufferMemoryRequirements = loader.load(
+            true,
+            vulkan_1_3,
+            &[
+                c"vkGetDeviceBufferMemoryRequirements",
+                c"vkGetDeviceBufferMemoryRequirementsKHR",
+            ],
+        );
+        table.vkGetDeviceImageMemoryRequirements = loader.load(
+            true,
+            vulkan_1_3,
+            &[
+                c"vkGetDeviceImageMemoryRequirements",
+                c"vkGetDeviceImageMemoryRequirementsKHR",
+            ],
+        );
+
+        if !loader.missing.is_empty() {
+            return Err(MissingFunctions(loader.missing));
+        }
+        Ok(functions)
+    }
+}
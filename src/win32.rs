@@ -0,0 +1,52 @@
+// Copyright (c) 2024 Jacob R. Green
+// All rights reserved.
+
+use crate::*;
+
+static OPAQUE_WIN32_HANDLE_TYPES: [vk::sys::VkExternalMemoryHandleTypeFlags;
+    vk::sys::VK_MAX_MEMORY_TYPES as usize] =
+    [vk::sys::VK_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT as _;
+        vk::sys::VK_MAX_MEMORY_TYPES as usize];
+
+impl AllocatorCreateInfo {
+    /// Makes every memory block exportable as an OPAQUE_WIN32 handle, for D3D12/DXGI interop.
+    /// Only the default pools pick this up; custom pools need their own export info.
+    pub fn with_opaque_win32_export(self) -> Self {
+        // The table is static, so it outlives any allocator.
+        unsafe { self.with_type_external_memory_handle_types(&OPAQUE_WIN32_HANDLE_TYPES) }
+    }
+}
+
+impl Allocator {
+    /// Exports the `VkDeviceMemory` backing `allocation` as an OPAQUE_WIN32 handle. The handle
+    /// refers to the whole memory block, so interop resources are best created with
+    /// `DEDICATED_MEMORY`. The caller owns the handle and must close it with `CloseHandle`.
+    pub fn get_memory_win32_handle(
+        &self,
+        allocation: Allocation,
+        get_memory_win32_handle: vk::sys::PFN_vkGetMemoryWin32HandleKHR,
+    ) -> Result<vk::sys::HANDLE, Error> {
+        let get_memory_win32_handle =
+            get_memory_win32_handle.ok_or(Error::MissingFunction("vkGetMemoryWin32HandleKHR"))?;
+
+        let get_info = vk::sys::VkMemoryGetWin32HandleInfoKHR {
+            sType: vk::sys::VK_STRUCTURE_TYPE_MEMORY_GET_WIN32_HANDLE_INFO_KHR,
+            pNext: std::ptr::null(),
+            memory: self
+                .get_allocation_info(allocation)
+                .device_memory()
+                .as_raw(),
+            handleType: vk::sys::VK_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32_BIT as _,
+        };
+
+        let mut handle = std::ptr::null_mut();
+        let result = unsafe {
+            get_memory_win32_handle(self.raw_allocator_info().device, &get_info, &mut handle)
+        };
+        if result != vk::sys::VK_SUCCESS {
+            return Err(Error::from_raw(result));
+        }
+
+        Ok(handle)
+    }
+}